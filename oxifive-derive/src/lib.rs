@@ -0,0 +1,166 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, punctuated::Punctuated, token::Comma, Attribute, Data, DeriveInput, Error,
+    Field, Fields, Ident, LitStr, Variant,
+};
+
+/// Derives `DatatypeVerifiable` so that a type can be read from a dataset.
+///
+/// Structs with named fields are read from compound datatypes, where fields
+/// are matched to compound members by name. Enums with unit variants are read
+/// from enumerated datatypes, where variants are matched to enumerated members
+/// by name. Names can be overridden with `#[oxifive(name = "...")]`.
+#[proc_macro_derive(H5Type, attributes(oxifive))]
+pub fn derive_h5type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn member_name(ident: &Ident, attributes: &[Attribute]) -> Result<String, Error> {
+    let mut name = ident.to_string();
+    for attribute in attributes {
+        if !attribute.path().is_ident("oxifive") {
+            continue;
+        }
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported oxifive attribute"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let (verify, decode) = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => expand_struct(&input.ident, &fields.named)?,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "H5Type can only be derived for structs with named fields",
+                ))
+            }
+        },
+        Data::Enum(data) => expand_enum(&input.ident, &data.variants)?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "H5Type can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::oxifive::read::dataset::DatatypeVerifiable
+            for #ident #type_generics #where_clause
+        {
+            fn verify(
+                datatype: &::oxifive::read::datatype::Datatype,
+            ) -> ::std::result::Result<(), ::oxifive::error::Error> {
+                #verify
+            }
+
+            fn decode(
+                bytes: &[u8],
+                datatype: &::oxifive::read::datatype::Datatype,
+                context: &mut ::oxifive::read::decode::DecodeContext,
+            ) -> ::std::result::Result<Self, ::oxifive::error::Error> {
+                #decode
+            }
+        }
+    })
+}
+
+fn expand_struct(
+    ident: &Ident,
+    fields: &Punctuated<Field, Comma>,
+) -> Result<(TokenStream2, TokenStream2), Error> {
+    let mut verify = vec![];
+    let mut decode = vec![];
+    for field in fields {
+        let field_ident = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+        let name = member_name(field_ident, &field.attrs)?;
+        verify.push(quote! {
+            datatype.compound_member(#name)?.verify::<#field_type>()?;
+        });
+        decode.push(quote! {
+            #field_ident: {
+                let member = datatype.compound_member(#name)?;
+                <#field_type as ::oxifive::read::dataset::DatatypeVerifiable>::decode(
+                    member.bytes(bytes)?,
+                    &member.datatype,
+                    context,
+                )?
+            },
+        });
+    }
+    Ok((
+        quote! {
+            #(#verify)*
+            Ok(())
+        },
+        quote! {
+            Ok(#ident {
+                #(#decode)*
+            })
+        },
+    ))
+}
+
+fn expand_enum(
+    ident: &Ident,
+    variants: &Punctuated<Variant, Comma>,
+) -> Result<(TokenStream2, TokenStream2), Error> {
+    let mut names = vec![];
+    let mut variant_idents = vec![];
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "H5Type can only be derived for enums with unit variants",
+            ));
+        }
+        names.push(member_name(&variant.ident, &variant.attrs)?);
+        variant_idents.push(&variant.ident);
+    }
+    let type_name = ident.to_string();
+    Ok((
+        // Every member in the file needs a variant, so that decoding never
+        // finds a value that cannot be represented.
+        quote! {
+            for member in datatype.enum_members()? {
+                match &member.name[..] {
+                    #(#names)|* => {}
+                    name => {
+                        return Err(::oxifive::error::Error::OxifiveError(format!(
+                            "Enumerated member {} has no variant in {}",
+                            name, #type_name
+                        )))
+                    }
+                }
+            }
+            Ok(())
+        },
+        quote! {
+            let _ = context;
+            match datatype.enum_member_name(bytes)? {
+                #(#names => Ok(#ident::#variant_idents),)*
+                name => Err(::oxifive::error::Error::OxifiveError(format!(
+                    "Enumerated member {} has no variant in {}",
+                    name, #type_name
+                ))),
+            }
+        },
+    ))
+}
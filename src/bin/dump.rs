@@ -57,8 +57,16 @@ fn main() -> anyhow::Result<()> {
                     let encoding = datatype.encoding;
                     println!("{:->dataset_indentation$} {key} [dataset shape {shape:?} type {encoding:?} size {size}]", "");
                 }
+                Object::Datatype(datatype) => {
+                    let datatype_indentation = indentation + 1;
+                    let encoding = datatype.encoding;
+                    println!(
+                        "{:->datatype_indentation$} {key} [datatype {encoding:?}]",
+                        ""
+                    );
+                }
             }
         }
     }
-    return Ok(());
+    Ok(())
 }
@@ -2,4 +2,5 @@ pub mod error;
 pub mod padding;
 pub mod read;
 
+pub use oxifive_derive::H5Type;
 pub use read::{dataset::Dataset, file::FileReader, group::Group, io::ReadSeek, object::Object};
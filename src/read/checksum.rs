@@ -0,0 +1,83 @@
+use crate::error::Error;
+
+fn mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+    *a = a.wrapping_sub(*c);
+    *a ^= c.rotate_left(4);
+    *c = c.wrapping_add(*b);
+    *b = b.wrapping_sub(*a);
+    *b ^= a.rotate_left(6);
+    *a = a.wrapping_add(*c);
+    *c = c.wrapping_sub(*b);
+    *c ^= b.rotate_left(8);
+    *b = b.wrapping_add(*a);
+    *a = a.wrapping_sub(*c);
+    *a ^= c.rotate_left(16);
+    *c = c.wrapping_add(*b);
+    *b = b.wrapping_sub(*a);
+    *b ^= a.rotate_left(19);
+    *a = a.wrapping_add(*c);
+    *c = c.wrapping_sub(*b);
+    *c ^= b.rotate_left(4);
+    *b = b.wrapping_add(*a);
+}
+
+fn final_mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(14));
+    *a ^= *c;
+    *a = a.wrapping_sub(c.rotate_left(11));
+    *b ^= *a;
+    *b = b.wrapping_sub(a.rotate_left(25));
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(16));
+    *a ^= *c;
+    *a = a.wrapping_sub(c.rotate_left(4));
+    *b ^= *a;
+    *b = b.wrapping_sub(a.rotate_left(14));
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(24));
+}
+
+fn read_word(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Bob Jenkins' lookup3 hash (`hashlittle`) with an initial value of zero,
+/// which is the checksum used by HDF5 for superblocks, object headers and
+/// other metadata introduced in format version 2.
+pub fn lookup3(bytes: &[u8]) -> u32 {
+    let initial = 0xdeadbeef_u32.wrapping_add(bytes.len() as u32);
+    let (mut a, mut b, mut c) = (initial, initial, initial);
+
+    let mut remaining = bytes;
+    while remaining.len() > 12 {
+        a = a.wrapping_add(read_word(&remaining[0..4]));
+        b = b.wrapping_add(read_word(&remaining[4..8]));
+        c = c.wrapping_add(read_word(&remaining[8..12]));
+        mix(&mut a, &mut b, &mut c);
+        remaining = &remaining[12..];
+    }
+
+    if remaining.is_empty() {
+        return c;
+    }
+
+    let mut tail = [0; 12];
+    tail[..remaining.len()].copy_from_slice(remaining);
+    a = a.wrapping_add(read_word(&tail[0..4]));
+    b = b.wrapping_add(read_word(&tail[4..8]));
+    c = c.wrapping_add(read_word(&tail[8..12]));
+    final_mix(&mut a, &mut b, &mut c);
+    c
+}
+
+pub fn verify_checksum(bytes: &[u8], expected: u32, name: &str) -> Result<(), Error> {
+    let actual = lookup3(bytes);
+    if actual != expected {
+        return Err(Error::OxifiveError(format!(
+            "Checksum mismatch for {}: expected {:#010x}, computed {:#010x}",
+            name, expected, actual
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use crate::error::Error;
+use crate::read::{
+    checksum::verify_checksum,
+    context::{FormatContext, UNDEFINED_ADDRESS},
+    data_storage::ChunkIndex,
+    node::parse_node,
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read, Seek};
+
+/// A chunk that has been written to the file.
+#[derive(Clone, Debug)]
+pub struct Chunk {
+    pub address: u64,
+    /// Size in the file, which is smaller than the chunk for compressed data.
+    pub size: u64,
+    /// Filters in the pipeline that were skipped for this chunk, one bit each.
+    pub filter_mask: u32,
+    /// Position of the first element of the chunk in the dataset.
+    pub offsets: Vec<u64>,
+}
+
+/// Finds the chunks of a dataset through its chunk index. The last dimension of
+/// `chunk_shape` is the size of the datatype, while `shape` and `max_shape` are
+/// the current and maximum shapes of the dataset. Chunks that were never
+/// written or that are outside the current shape are left out.
+pub fn read_chunk_index(
+    input: &mut (impl Read + Seek + ?Sized),
+    context: &FormatContext,
+    index: &ChunkIndex,
+    address: u64,
+    chunk_shape: &[u32],
+    shape: &[u64],
+    max_shape: &[Option<u64>],
+) -> Result<Vec<Chunk>, Error> {
+    // Datasets without any written chunks have no index, whatever its type
+    if address == UNDEFINED_ADDRESS {
+        return Ok(vec![]);
+    }
+    let chunk_size: u64 = chunk_shape.iter().map(|&x| u64::from(x)).product();
+    // The implicit and fixed array indexes hold every chunk up to the maximum
+    // shape, which these indexes require to be limited
+    let max_shape: Vec<u64> = shape
+        .iter()
+        .zip(max_shape)
+        .map(|(&size, max_size)| max_size.unwrap_or(size))
+        .collect();
+    let grid = ChunkGrid::new(&chunk_shape[..chunk_shape.len() - 1], &max_shape)?;
+    let chunks = match index {
+        ChunkIndex::BTreeV1 => read_btree_v1(input, context, address, chunk_shape.len()),
+        ChunkIndex::SingleChunk { filtered } => {
+            let (size, filter_mask) = filtered.unwrap_or((chunk_size, 0));
+            Ok(vec![Chunk {
+                address,
+                size,
+                filter_mask,
+                offsets: vec![0; shape.len()],
+            }])
+        }
+        ChunkIndex::Implicit => Ok((0..grid.count())
+            .map(|index| Chunk {
+                address: address + index * chunk_size,
+                size: chunk_size,
+                filter_mask: 0,
+                offsets: grid.offsets(index),
+            })
+            .collect()),
+        ChunkIndex::FixedArray { page_bits } => {
+            let entries = read_fixed_array(input, context, address, *page_bits)?;
+            if entries.len() as u64 != grid.count() {
+                return Err(Error::OxifiveError(format!(
+                    "Fixed array has {} entries, but the dataset has {} chunks",
+                    entries.len(),
+                    grid.count()
+                )));
+            }
+            Ok(entries
+                .into_iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.address != UNDEFINED_ADDRESS)
+                .map(|(index, entry)| Chunk {
+                    address: entry.address,
+                    size: entry.size.unwrap_or(chunk_size),
+                    filter_mask: entry.filter_mask,
+                    offsets: grid.offsets(index as u64),
+                })
+                .collect())
+        }
+        ChunkIndex::Unsupported(index_type) => Err(Error::OxifiveError(format!(
+            "Unsupported chunk index type {:?}",
+            index_type
+        ))),
+    }?;
+    // Chunks are kept when a dataset shrinks, in case it grows again
+    Ok(chunks
+        .into_iter()
+        .filter(|chunk| {
+            chunk
+                .offsets
+                .iter()
+                .zip(shape)
+                .all(|(offset, size)| offset < size)
+        })
+        .collect())
+}
+
+/// The number of chunks along each dimension, which are numbered in row-major
+/// order by the implicit and fixed array indexes.
+struct ChunkGrid {
+    chunk_shape: Vec<u64>,
+    counts: Vec<u64>,
+}
+
+impl ChunkGrid {
+    fn new(chunk_shape: &[u32], shape: &[u64]) -> Result<Self, Error> {
+        if chunk_shape.len() != shape.len() || chunk_shape.contains(&0) {
+            return Err(Error::OxifiveError(format!(
+                "Chunk shape {:?} does not match dataset shape {:?}",
+                chunk_shape, shape
+            )));
+        }
+        let chunk_shape: Vec<u64> = chunk_shape.iter().map(|&x| u64::from(x)).collect();
+        let counts = shape
+            .iter()
+            .zip(&chunk_shape)
+            .map(|(&size, &chunk)| size.div_ceil(chunk))
+            .collect();
+        Ok(ChunkGrid {
+            chunk_shape,
+            counts,
+        })
+    }
+
+    fn count(&self) -> u64 {
+        self.counts.iter().product()
+    }
+
+    fn offsets(&self, mut index: u64) -> Vec<u64> {
+        let mut offsets = vec![0; self.counts.len()];
+        for dimension in (0..self.counts.len()).rev() {
+            offsets[dimension] = (index % self.counts[dimension]) * self.chunk_shape[dimension];
+            index /= self.counts[dimension];
+        }
+        offsets
+    }
+}
+
+fn read_btree_v1(
+    input: &mut (impl Read + Seek + ?Sized),
+    context: &FormatContext,
+    address: u64,
+    dimensions: usize,
+) -> Result<Vec<Chunk>, Error> {
+    let mut nodes = vec![parse_node(input, context, address, dimensions)?];
+    while nodes[0].node_level != 0 {
+        let mut children = vec![];
+        for node in &nodes {
+            for key in &node.keys {
+                children.push(parse_node(input, context, key.chunk_address, dimensions)?);
+            }
+        }
+        nodes = children;
+    }
+    Ok(nodes
+        .into_iter()
+        .flat_map(|node| node.keys)
+        .map(|key| Chunk {
+            address: key.chunk_address,
+            size: u64::from(key.chunk_size),
+            filter_mask: key.filter_mask,
+            // The last offset is always zero, for the datatype dimension
+            offsets: key.chunk_offsets[..dimensions - 1].to_vec(),
+        })
+        .collect())
+}
+
+struct FixedArrayEntry {
+    address: u64,
+    size: Option<u64>,
+    filter_mask: u32,
+}
+
+fn read_signature(input: &mut impl Read, expected: &str) -> Result<(), Error> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    if bytes != expected.as_bytes() {
+        return Err(Error::OxifiveError(format!(
+            "Wrong signature {:?}, expected {}",
+            String::from_utf8_lossy(&bytes),
+            expected
+        )));
+    }
+    Ok(())
+}
+
+/// Reads a structure followed by its checksum and verifies it.
+fn read_checksummed(
+    input: &mut (impl Read + ?Sized),
+    size: usize,
+    name: &str,
+) -> Result<Cursor<Vec<u8>>, Error> {
+    let mut bytes = vec![0; size];
+    input.read_exact(&mut bytes)?;
+    let checksum = input.read_u32::<LittleEndian>()?;
+    verify_checksum(&bytes, checksum, name)?;
+    Ok(Cursor::new(bytes))
+}
+
+fn read_fixed_array(
+    input: &mut (impl Read + Seek + ?Sized),
+    context: &FormatContext,
+    address: u64,
+    page_bits: u8,
+) -> Result<Vec<FixedArrayEntry>, Error> {
+    let offset_size = context.offset_size as usize;
+    let length_size = context.length_size as usize;
+
+    context.seek(input, address)?;
+    let mut header = read_checksummed(input, 8 + length_size + offset_size, "fixed array header")?;
+    read_signature(&mut header, "FAHD")?;
+    let version = header.read_u8()?;
+    if version != 0 {
+        return Err(Error::OxifiveError(format!(
+            "Unsupported fixed array header version {}",
+            version
+        )));
+    }
+    let client = header.read_u8()?;
+    let entry_size = header.read_u8()? as usize;
+    let header_page_bits = header.read_u8()?;
+    let entry_count = context.read_length(&mut header)? as usize;
+    let data_block_address = context.read_offset(&mut header)?;
+    if header_page_bits != page_bits {
+        return Err(Error::OxifiveError(format!(
+            "Fixed array page bits {} differ from the layout message {}",
+            header_page_bits, page_bits
+        )));
+    }
+    // Filtered chunks store their size and filter mask after the address
+    let filtered = match client {
+        0 => false,
+        1 => true,
+        _ => {
+            return Err(Error::OxifiveError(format!(
+                "Unsupported fixed array client {}",
+                client
+            )))
+        }
+    };
+    let filtered_size_length = if filtered {
+        match entry_size.checked_sub(offset_size + 4) {
+            Some(length @ 1..=8) => length,
+            _ => {
+                return Err(Error::OxifiveError(format!(
+                    "Invalid fixed array entry size {}",
+                    entry_size
+                )))
+            }
+        }
+    } else {
+        0
+    };
+    let read_entries = |cursor: &mut Cursor<Vec<u8>>, count: usize| {
+        (0..count)
+            .map(|_| {
+                let address = context.read_offset(cursor)?;
+                if !filtered {
+                    return Ok(FixedArrayEntry {
+                        address,
+                        size: None,
+                        filter_mask: 0,
+                    });
+                }
+                let size = cursor.read_uint::<LittleEndian>(filtered_size_length)?;
+                Ok(FixedArrayEntry {
+                    address,
+                    size: Some(size),
+                    filter_mask: cursor.read_u32::<LittleEndian>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    };
+
+    if data_block_address == UNDEFINED_ADDRESS {
+        return Ok((0..entry_count)
+            .map(|_| FixedArrayEntry {
+                address: UNDEFINED_ADDRESS,
+                size: None,
+                filter_mask: 0,
+            })
+            .collect());
+    }
+
+    let page_entries = 1usize << page_bits;
+    let paged = entry_count > page_entries;
+    let page_count = entry_count.div_ceil(page_entries);
+    // Paged data blocks have a bitmap of initialized pages instead of entries
+    let prefix_size = 6 + offset_size;
+    let block_size = if paged {
+        prefix_size + page_count.div_ceil(8)
+    } else {
+        prefix_size + entry_count * entry_size
+    };
+    context.seek(input, data_block_address)?;
+    let mut block = read_checksummed(input, block_size, "fixed array data block")?;
+    read_signature(&mut block, "FADB")?;
+    let version = block.read_u8()?;
+    if version != 0 {
+        return Err(Error::OxifiveError(format!(
+            "Unsupported fixed array data block version {}",
+            version
+        )));
+    }
+    let _client = block.read_u8()?;
+    let _header_address = context.read_offset(&mut block)?;
+    if !paged {
+        return read_entries(&mut block, entry_count);
+    }
+
+    let mut bitmap = vec![0; page_count.div_ceil(8)];
+    block.read_exact(&mut bitmap)?;
+    // Pages follow the data block, each with its own checksum
+    let pages_address = data_block_address + block_size as u64 + 4;
+    let page_size = page_entries * entry_size + 4;
+    let mut entries = vec![];
+    for page in 0..page_count {
+        let count = page_entries.min(entry_count - page * page_entries);
+        if bitmap[page / 8] & (0x80 >> (page % 8)) == 0 {
+            entries.extend((0..count).map(|_| FixedArrayEntry {
+                address: UNDEFINED_ADDRESS,
+                size: None,
+                filter_mask: 0,
+            }));
+            continue;
+        }
+        context.seek(input, pages_address + (page * page_size) as u64)?;
+        let mut cursor = read_checksummed(input, count * entry_size, "fixed array page")?;
+        entries.extend(read_entries(&mut cursor, count)?);
+    }
+    Ok(entries)
+}
@@ -0,0 +1,98 @@
+use crate::error::Error;
+use crate::read::{data_object::DataObject, superblock::SuperBlock};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Default indexed storage internal node K used by files that do not store
+/// their own value in the superblock.
+const DEFAULT_CHUNK_BTREE_K: u16 = 32;
+
+/// Addresses with all bits set are undefined. They are normalized to this
+/// value regardless of the size of offsets in the file.
+pub const UNDEFINED_ADDRESS: u64 = u64::MAX;
+
+/// File-wide parameters from the superblock that are needed when parsing
+/// structures elsewhere in the file.
+#[derive(Clone, Copy, Debug)]
+pub struct FormatContext {
+    /// Absolute position in the file that all addresses are relative to.
+    pub base_address: u64,
+    pub offset_size: u8,
+    pub length_size: u8,
+    pub chunk_btree_k: u16,
+}
+
+fn verify_size(size: u8, name: &str) -> Result<(), Error> {
+    match size {
+        2 | 4 | 8 => Ok(()),
+        _ => Err(Error::OxifiveError(format!(
+            "Unsupported size of {}: {} bytes",
+            name, size
+        ))),
+    }
+}
+
+impl FormatContext {
+    pub fn new(offset_size: u8, length_size: u8) -> Result<Self, Error> {
+        verify_size(offset_size, "offsets")?;
+        verify_size(length_size, "lengths")?;
+        Ok(FormatContext {
+            base_address: 0,
+            offset_size,
+            length_size,
+            chunk_btree_k: DEFAULT_CHUNK_BTREE_K,
+        })
+    }
+
+    pub fn from_superblock(
+        superblock: &SuperBlock,
+        superblock_address: u64,
+        superblock_extension: Option<&DataObject>,
+    ) -> Result<Self, Error> {
+        let mut context = FormatContext::new(superblock.offset_size(), superblock.length_size())?;
+        context.base_address = superblock_address;
+        let chunk_btree_k = match superblock {
+            SuperBlock::Version0(superblock) => superblock.indexed_storage_internal_node_k,
+            SuperBlock::Version2(_) => superblock_extension
+                .and_then(|extension| extension.btree_k_values.as_ref())
+                .map(|values| values.indexed_storage_internal_node_k),
+        };
+        if let Some(chunk_btree_k) = chunk_btree_k {
+            context.chunk_btree_k = chunk_btree_k;
+        }
+        Ok(context)
+    }
+
+    /// Seeks to an address, which is relative to the base address. Undefined
+    /// addresses refer to storage that was never allocated and are an error.
+    pub fn seek(&self, input: &mut (impl Seek + ?Sized), address: u64) -> Result<(), Error> {
+        if address == UNDEFINED_ADDRESS {
+            return Err(Error::OxifiveError(
+                "Cannot seek to an undefined address".to_string(),
+            ));
+        }
+        let position = self.base_address.checked_add(address).ok_or_else(|| {
+            Error::OxifiveError(format!(
+                "Address {} overflows when added to the base address {}",
+                address, self.base_address
+            ))
+        })?;
+        input.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    /// Reads an address, which is stored using the size of offsets.
+    pub fn read_offset(&self, input: &mut (impl Read + ?Sized)) -> Result<u64, Error> {
+        let value = input.read_uint::<LittleEndian>(self.offset_size as usize)?;
+        if value == u64::MAX >> (64 - 8 * self.offset_size as u32) {
+            Ok(UNDEFINED_ADDRESS)
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Reads a length or size, which is stored using the size of lengths.
+    pub fn read_length(&self, input: &mut (impl Read + ?Sized)) -> Result<u64, Error> {
+        Ok(input.read_uint::<LittleEndian>(self.length_size as usize)?)
+    }
+}
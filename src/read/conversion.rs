@@ -0,0 +1,130 @@
+use crate::error::Error;
+use crate::read::datatype::{Datatype, DatatypeProperties};
+use num_traits::{AsPrimitive, Bounded};
+use std::convert::TryFrom;
+
+/// How values are handled that do not fit the numeric Rust type that a dataset
+/// is read into.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConversionPolicy {
+    /// Fails the read on overflow, or on fractional and non-finite values read
+    /// into integers and integers that a float cannot represent exactly.
+    #[default]
+    Error,
+    /// Clamps values to the range of the Rust type, truncates fractions and
+    /// rounds integers to the nearest float.
+    Saturate,
+    /// Wraps integers around as with `as` casts, truncates fractions and rounds
+    /// integers to the nearest float. Floats that overflow become infinite.
+    Wrap,
+}
+
+/// A fixed-point or floating-point value decoded from the file, before it is
+/// converted to the Rust type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    Integer(i128),
+    Float(f64),
+}
+
+impl Number {
+    /// Decodes a fixed-point, floating-point or enumerated element.
+    pub fn decode(bytes: &[u8], datatype: &Datatype) -> Result<Number, Error> {
+        match &datatype.properties {
+            DatatypeProperties::FixedPoint { signed, .. } => {
+                let value = datatype.decode_fixed_point(bytes)?;
+                Ok(Number::Integer(if *signed {
+                    i128::from(value as i64)
+                } else {
+                    i128::from(value)
+                }))
+            }
+            DatatypeProperties::FloatingPoint { .. } => {
+                Ok(Number::Float(datatype.decode_floating_point(bytes)?))
+            }
+            DatatypeProperties::Enumerated { base_type, .. } => Number::decode(bytes, base_type),
+            _ => Err(Error::OxifiveError(format!(
+                "Expected a numeric datatype, found {:?}",
+                datatype
+            ))),
+        }
+    }
+}
+
+fn overflow<T>(value: Number, name: &str) -> Result<T, Error> {
+    Err(Error::OxifiveError(format!(
+        "Value {:?} overflows {}",
+        value, name
+    )))
+}
+
+fn precision_loss<T>(value: Number, name: &str) -> Result<T, Error> {
+    Err(Error::OxifiveError(format!(
+        "Value {:?} cannot be represented exactly by {}",
+        value, name
+    )))
+}
+
+/// Converts a number to an integer type.
+pub fn to_integer<T>(value: Number, policy: ConversionPolicy, name: &str) -> Result<T, Error>
+where
+    T: Bounded + TryFrom<i128> + Copy + 'static,
+    i128: AsPrimitive<T>,
+    f64: AsPrimitive<T>,
+{
+    let integer = match value {
+        Number::Integer(integer) => integer,
+        Number::Float(float) if float.is_finite() && float.fract() == 0.0 => float as i128,
+        Number::Float(float) => {
+            return match policy {
+                ConversionPolicy::Error => precision_loss(value, name),
+                // Casts from floats saturate, with NaN becoming zero
+                ConversionPolicy::Saturate => Ok(float.as_()),
+                ConversionPolicy::Wrap => Ok((float as i128).as_()),
+            };
+        }
+    };
+    match T::try_from(integer) {
+        Ok(converted) => Ok(converted),
+        Err(_) => match policy {
+            ConversionPolicy::Error => overflow(value, name),
+            ConversionPolicy::Saturate if integer < 0 => Ok(T::min_value()),
+            ConversionPolicy::Saturate => Ok(T::max_value()),
+            ConversionPolicy::Wrap => Ok(integer.as_()),
+        },
+    }
+}
+
+/// Converts a number to a float type, given as conversions from and to f64 and
+/// its largest finite value.
+pub fn to_float<T: Copy>(
+    value: Number,
+    policy: ConversionPolicy,
+    name: &str,
+    from_f64: fn(f64) -> T,
+    to_f64: fn(T) -> f64,
+    max: f64,
+) -> Result<T, Error> {
+    let float = match value {
+        Number::Integer(integer) => integer as f64,
+        Number::Float(float) => float,
+    };
+    if float.is_finite() && float.abs() > max {
+        return match policy {
+            ConversionPolicy::Error => overflow(value, name),
+            ConversionPolicy::Saturate => Ok(from_f64(max.copysign(float))),
+            ConversionPolicy::Wrap => Ok(from_f64(f64::INFINITY.copysign(float))),
+        };
+    }
+    let converted = from_f64(float);
+    // Rounding between float types is expected, but integers are only read
+    // exactly unless the policy allows rounding
+    if let Number::Integer(integer) = value {
+        if policy == ConversionPolicy::Error
+            && (float as i128 != integer || to_f64(converted) != float)
+        {
+            return precision_loss(value, name);
+        }
+    }
+    Ok(converted)
+}
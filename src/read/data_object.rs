@@ -1,14 +1,19 @@
+use crate::read::context::FormatContext;
 use crate::read::data_storage::{parse_data_storage_message, DataStorage};
 use crate::read::dataspace::{parse_dataspace_message, Dataspace};
 use crate::read::datatype::{parse_datatype_message, Datatype};
+use crate::read::fill_value::{parse_fill_value_message, parse_old_fill_value_message};
 use crate::read::filter_pipeline::{parse_filter_pipeline_message, FilterPipeline};
 use crate::read::io::ReadSeek;
 use crate::read::link::{parse_link_message, parse_symbol_table_message};
+use crate::read::superblock::{parse_btree_k_values_message, BTreeKValues};
 use crate::{
     error::Error,
     read::link::Link,
-    read::group::Group,
-    read::message::{MessageHeaderV1, MessageHeaderV2, MessageType},
+    read::message::{
+        parse_shared_message_address, MessageHeaderV1, MessageHeaderV2, MessageType,
+        SHARED_MESSAGE_FLAG,
+    },
 };
 use bitflags::bitflags;
 use byteorder::{LittleEndian, ReadBytesExt};
@@ -18,8 +23,6 @@ use std::{
     io::{Cursor, Read, Seek, SeekFrom},
 };
 
-use super::dataset::Dataset;
-
 bitflags! {
     struct ObjectHeaderFlags: u8 {
         const SIZE_OF_CHUNK_BIT_A = 0b000001;
@@ -31,30 +34,15 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Debug)]
-struct ObjectHeaderTimes {
-    access: u32,
-    modification: u32,
-    change: u32,
-    birth: u32,
-}
-
 #[derive(Clone, Debug)]
 struct ObjectHeaderV1 {
-    version: u8,
-    reserved: u8,
     total_header_messages: u16,
-    object_reference_count: u32,
     object_header_size: u32,
-    padding: u32,
 }
 
 #[derive(Clone, Debug)]
 struct ObjectHeader {
-    signature: [u8; 4],
-    version: u8,
     flags: ObjectHeaderFlags,
-    times: Option<ObjectHeaderTimes>,
     size_of_chunk_0: u64, // TODO look for better way of filling out struct with all info over time...
 }
 
@@ -65,23 +53,57 @@ pub struct DataObject {
     pub datatypes: Vec<Datatype>,
     pub dataspaces: Vec<Dataspace>,
     pub filter_pipelines: Vec<FilterPipeline>,
+    pub btree_k_values: Option<BTreeKValues>,
+    /// Bytes of the value of elements that were never written, which are
+    /// zeros if `None`.
+    pub fill_value: Option<Vec<u8>>,
 }
 
 impl DataObject {
     pub fn is_group(&self) -> bool {
-        // if no dataspaces were found in messages, the object should be a group
-        self.dataspaces.is_empty()
+        // if no dataspaces or datatypes were found in messages, the object
+        // should be a group
+        self.dataspaces.is_empty() && self.datatypes.is_empty()
+    }
+
+    /// Whether this is a committed datatype, which has a datatype but no
+    /// dataspace.
+    pub fn is_datatype(&self) -> bool {
+        self.dataspaces.is_empty() && !self.datatypes.is_empty()
     }
 
     pub fn is_dataset(&self) -> bool {
-        !self.is_group()
+        !self.dataspaces.is_empty()
+    }
+}
+
+/// Parses a datatype message, resolving shared messages to the definition in
+/// the committed datatype they refer to.
+fn parse_datatype(
+    input: &mut impl ReadSeek,
+    context: &FormatContext,
+    message_cursor: &mut Cursor<Vec<u8>>,
+    flags: u8,
+) -> Result<Datatype, Error> {
+    if flags & SHARED_MESSAGE_FLAG == 0 {
+        return parse_datatype_message(message_cursor);
     }
+    let address = parse_shared_message_address(message_cursor, context)?;
+    let committed = parse_data_object(input, context, address)?;
+    committed.datatypes.into_iter().next().ok_or_else(|| {
+        Error::OxifiveError(format!(
+            "Shared datatype at {} refers to an object without a datatype",
+            address
+        ))
+    })
 }
 
 fn parse_message(
     mut input: &mut impl ReadSeek,
+    context: &FormatContext,
     current_message_data: Vec<u8>,
     message_type: MessageType,
+    flags: u8,
     data_object: &mut DataObject,
     chunks: &mut Vec<Vec<u8>>,
 ) -> Result<(), Error> {
@@ -89,10 +111,10 @@ fn parse_message(
 
     match message_type {
         MessageType::ObjectContinuation => {
-            let offset = current_message_cursor.read_u64::<LittleEndian>()?;
-            let size = current_message_cursor.read_u64::<LittleEndian>()?;
+            let offset = context.read_offset(&mut current_message_cursor)?;
+            let size = context.read_length(&mut current_message_cursor)?;
             log::info!("Cont {} {}", offset, size);
-            input.seek(SeekFrom::Start(offset))?;
+            context.seek(input, offset)?;
             let mut chunk_signature = vec![0; 4];
             input.read_exact(&mut chunk_signature)?;
             if chunk_signature != b"OCHK" {
@@ -106,23 +128,28 @@ fn parse_message(
             chunks.push(continuation_chunk);
         }
         MessageType::Link => {
-            let link = parse_link_message(&mut current_message_cursor)?;
+            let link = parse_link_message(&mut current_message_cursor, context)?;
             data_object.links.insert(link.name.clone(), link);
         }
         MessageType::DataStorage => {
-            data_object
-                .data
-                .push(parse_data_storage_message(&mut current_message_cursor)?);
+            data_object.data.push(parse_data_storage_message(
+                &mut current_message_cursor,
+                context,
+            )?);
         }
         MessageType::Datatype => {
-            data_object
-                .datatypes
-                .push(parse_datatype_message(&mut current_message_cursor)?);
+            data_object.datatypes.push(parse_datatype(
+                input,
+                context,
+                &mut current_message_cursor,
+                flags,
+            )?);
         }
         MessageType::Dataspace => {
-            data_object
-                .dataspaces
-                .push(parse_dataspace_message(&mut current_message_cursor)?);
+            data_object.dataspaces.push(parse_dataspace_message(
+                &mut current_message_cursor,
+                context,
+            )?);
         }
         MessageType::DataStorageFilterPipeline => {
             data_object
@@ -130,13 +157,24 @@ fn parse_message(
                 .extend(parse_filter_pipeline_message(&mut current_message_cursor)?);
         }
         MessageType::SymbolTable => {
-            let links = parse_symbol_table_message(&mut input, &mut current_message_cursor)?;
+            let links =
+                parse_symbol_table_message(&mut input, context, &mut current_message_cursor)?;
             data_object
                 .links
                 .extend(links.iter().map(|l| (l.name.clone(), l.clone())));
         }
+        MessageType::BtreeKValue => {
+            data_object.btree_k_values =
+                Some(parse_btree_k_values_message(&mut current_message_cursor)?);
+        }
         MessageType::Fillvalue => {
-            // TODO this should not just be ignored
+            data_object.fill_value = parse_fill_value_message(&mut current_message_cursor)?;
+        }
+        MessageType::FillvalueOld => {
+            let fill_value = parse_old_fill_value_message(&mut current_message_cursor)?;
+            if data_object.fill_value.is_none() {
+                data_object.fill_value = fill_value;
+            }
         }
         MessageType::ObjectModificationTime => {
             // TODO this should not just be ignored
@@ -154,7 +192,11 @@ fn parse_message(
     Ok(())
 }
 
-fn parse_v1_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataObject, Error> {
+fn parse_v1_objects(
+    version_hint: u8,
+    input: &mut impl ReadSeek,
+    context: &FormatContext,
+) -> Result<DataObject, Error> {
     let object_header = {
         let version = version_hint;
 
@@ -165,13 +207,14 @@ fn parse_v1_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
             )));
         }
 
+        let _reserved = input.read_u8()?;
+        let total_header_messages = input.read_u16::<LittleEndian>()?;
+        let _object_reference_count = input.read_u32::<LittleEndian>()?;
+        let object_header_size = input.read_u32::<LittleEndian>()?;
+        let _padding = input.read_u32::<LittleEndian>()?;
         ObjectHeaderV1 {
-            version,
-            reserved: input.read_u8()?,
-            total_header_messages: input.read_u16::<LittleEndian>()?,
-            object_reference_count: input.read_u32::<LittleEndian>()?,
-            object_header_size: input.read_u32::<LittleEndian>()?,
-            padding: input.read_u32::<LittleEndian>()?,
+            total_header_messages,
+            object_header_size,
         }
     };
 
@@ -188,6 +231,8 @@ fn parse_v1_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
         datatypes: Vec::new(),
         dataspaces: Vec::new(),
         filter_pipelines: Vec::new(),
+        btree_k_values: None,
+        fill_value: None,
     };
     let mut current_chunk_index = 0;
     while current_chunk_index < chunks.len() {
@@ -198,11 +243,11 @@ fn parse_v1_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
                 // TODO verify safety of cast from u16 to u8
                 message_type: MessageType::try_from(data_cursor.read_u16::<LittleEndian>()? as u8)?,
                 size: data_cursor.read_u16::<LittleEndian>()?,
-                flags: data_cursor.read_u8()?,
-                reserved: {
-                    let mut result = [0; 3];
-                    data_cursor.read_exact(&mut result)?;
-                    result
+                flags: {
+                    let flags = data_cursor.read_u8()?;
+                    let mut reserved = [0; 3];
+                    data_cursor.read_exact(&mut reserved)?;
+                    flags
                 },
             };
             log::info!("{:#?}", message_header);
@@ -215,8 +260,10 @@ fn parse_v1_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
 
             parse_message(
                 input,
+                context,
                 current_message_data,
                 message_header.message_type,
+                message_header.flags,
                 &mut data_object,
                 &mut chunks,
             )?;
@@ -227,7 +274,11 @@ fn parse_v1_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
     Ok(data_object)
 }
 
-fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataObject, Error> {
+fn parse_v2_objects(
+    version_hint: u8,
+    input: &mut impl ReadSeek,
+    context: &FormatContext,
+) -> Result<DataObject, Error> {
     let object_header = {
         let signature = [
             version_hint,
@@ -235,6 +286,12 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
             input.read_u8()?,
             input.read_u8()?,
         ];
+        if &signature != b"OHDR" {
+            return Err(Error::OxifiveError(format!(
+                "Wrong object header signature: {:?}",
+                signature
+            )));
+        }
         let version = input.read_u8()?;
         let flags = ObjectHeaderFlags {
             bits: input.read_u8()?,
@@ -253,17 +310,11 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
             ));
         }
 
-        let times =
-            if flags.contains(ObjectHeaderFlags::ACCESS_MODIFICATION_CHANGE_AND_BIRTH_TRACKED) {
-                Some(ObjectHeaderTimes {
-                    access: input.read_u32::<LittleEndian>()?,
-                    modification: input.read_u32::<LittleEndian>()?,
-                    change: input.read_u32::<LittleEndian>()?,
-                    birth: input.read_u32::<LittleEndian>()?,
-                })
-            } else {
-                None
-            };
+        if flags.contains(ObjectHeaderFlags::ACCESS_MODIFICATION_CHANGE_AND_BIRTH_TRACKED) {
+            // Access, modification, change and birth times are not exposed
+            let mut times = [0; 16];
+            input.read_exact(&mut times)?;
+        }
 
         let size_of_chunk_field_bits_value = (flags
             & (ObjectHeaderFlags::SIZE_OF_CHUNK_BIT_A | ObjectHeaderFlags::SIZE_OF_CHUNK_BIT_B))
@@ -280,10 +331,7 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
             }
         };
         ObjectHeader {
-            signature,
-            version,
             flags,
-            times,
             size_of_chunk_0,
         }
     };
@@ -302,6 +350,8 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
     let mut datatypes = Vec::new();
     let mut dataspaces = Vec::new();
     let mut filter_pipelines = Vec::new();
+    let mut btree_k_values = None;
+    let mut fill_value = None;
     let mut current_chunk_index = 0;
     while current_chunk_index < chunks.len() {
         let current_chunk = chunks[current_chunk_index].clone();
@@ -330,10 +380,10 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
 
             match message_header.message_type {
                 MessageType::ObjectContinuation => {
-                    let offset = current_message_cursor.read_u64::<LittleEndian>()?;
-                    let size = current_message_cursor.read_u64::<LittleEndian>()?;
+                    let offset = context.read_offset(&mut current_message_cursor)?;
+                    let size = context.read_length(&mut current_message_cursor)?;
                     log::info!("Cont {} {}", offset, size);
-                    input.seek(SeekFrom::Start(offset))?;
+                    context.seek(input, offset)?;
                     let mut chunk_signature = vec![0; 4];
                     input.read_exact(&mut chunk_signature)?;
                     if chunk_signature != b"OCHK" {
@@ -347,22 +397,47 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
                     chunks.push(continuation_chunk);
                 }
                 MessageType::Link => {
-                    let link = parse_link_message(&mut current_message_cursor)?;
+                    let link = parse_link_message(&mut current_message_cursor, context)?;
                     links.insert(link.name.clone(), link);
                 }
                 MessageType::DataStorage => {
-                    data.push(parse_data_storage_message(&mut current_message_cursor)?);
+                    data.push(parse_data_storage_message(
+                        &mut current_message_cursor,
+                        context,
+                    )?);
                 }
                 MessageType::Datatype => {
-                    datatypes.push(parse_datatype_message(&mut current_message_cursor)?);
+                    datatypes.push(parse_datatype(
+                        input,
+                        context,
+                        &mut current_message_cursor,
+                        message_header.flags,
+                    )?);
                 }
                 MessageType::Dataspace => {
-                    dataspaces.push(parse_dataspace_message(&mut current_message_cursor)?);
+                    dataspaces.push(parse_dataspace_message(
+                        &mut current_message_cursor,
+                        context,
+                    )?);
                 }
                 MessageType::DataStorageFilterPipeline => {
                     filter_pipelines
                         .extend(parse_filter_pipeline_message(&mut current_message_cursor)?);
                 }
+                MessageType::BtreeKValue => {
+                    btree_k_values =
+                        Some(parse_btree_k_values_message(&mut current_message_cursor)?);
+                }
+                MessageType::Fillvalue => {
+                    fill_value = parse_fill_value_message(&mut current_message_cursor)?;
+                }
+                // The fill value message takes precedence over the old one
+                MessageType::FillvalueOld => {
+                    let old_fill_value = parse_old_fill_value_message(&mut current_message_cursor)?;
+                    if fill_value.is_none() {
+                        fill_value = old_fill_value;
+                    }
+                }
                 _ => {
                     // TODO handle all message types
                 }
@@ -377,21 +452,27 @@ fn parse_v2_objects(version_hint: u8, input: &mut impl ReadSeek) -> Result<DataO
         datatypes,
         dataspaces,
         filter_pipelines,
+        btree_k_values,
+        fill_value,
     })
 }
 
-pub fn parse_data_object(input: &mut (impl ReadSeek + Sized), offset: u64) -> Result<DataObject, Error> {
-    input.seek(SeekFrom::Start(offset))?;
+pub fn parse_data_object(
+    input: &mut (impl ReadSeek + Sized),
+    context: &FormatContext,
+    offset: u64,
+) -> Result<DataObject, Error> {
+    context.seek(input, offset)?;
     let version_hint = input.read_u8()?;
     log::info!("Version hint: {:#?}", version_hint);
     if version_hint == 1 {
-        parse_v1_objects(version_hint, input)
+        parse_v1_objects(version_hint, input, context)
     } else if version_hint == b'O' {
-        parse_v2_objects(version_hint, input)
+        parse_v2_objects(version_hint, input, context)
     } else {
-        return Err(Error::OxifiveError(format!(
+        Err(Error::OxifiveError(format!(
             "Unsupported data object version hint found: {}",
             version_hint
-        )));
+        )))
     }
 }
@@ -1,4 +1,5 @@
 use crate::error::Error;
+use crate::read::context::FormatContext;
 use byteorder::{LittleEndian, ReadBytesExt};
 use num_enum::TryFromPrimitive;
 use std::convert::TryFrom;
@@ -12,15 +13,64 @@ enum LayoutClass {
     Chunked = 2,
 }
 
+/// How the chunks of a chunked dataset are located in the file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChunkIndex {
+    /// A version 1 B-tree, used by layout messages before version 4.
+    BTreeV1,
+    /// A dataset stored as one chunk at the index address, which has a
+    /// filtered size and filter mask if filters are applied.
+    SingleChunk { filtered: Option<(u64, u32)> },
+    /// Unfiltered chunks of full size stored contiguously in row-major order.
+    Implicit,
+    /// A fixed array of chunk addresses, split into pages of `1 << page_bits`
+    /// entries.
+    FixedArray { page_bits: u8 },
+    /// An index that is parsed but cannot be read yet, so that the dataset can
+    /// still be opened and its shape inspected.
+    Unsupported(ChunkIndexType),
+}
+
+#[repr(u8)]
+#[derive(Clone, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum ChunkIndexType {
+    SingleChunk = 1,
+    Implicit = 2,
+    FixedArray = 3,
+    ExtensibleArray = 4,
+    BTreeV2 = 5,
+}
+
+const DONT_FILTER_PARTIAL_BOUND_CHUNKS: u8 = 0b0000_0001;
+const SINGLE_INDEX_WITH_FILTER: u8 = 0b0000_0010;
+
 #[derive(Clone, Debug)]
 pub enum DataStorage {
-    Contiguous { address: u64, size: u64 },
-    Chunked { chunk_shape: Vec<u32>, address: u64 },
+    /// Raw data stored in the layout message itself, for small datasets.
+    Compact {
+        data: Vec<u8>,
+    },
+    Contiguous {
+        address: u64,
+        size: u64,
+    },
+    /// Chunks of `chunk_shape`, whose last dimension is the size of the
+    /// datatype, located through the index at `address`.
+    Chunked {
+        chunk_shape: Vec<u32>,
+        address: u64,
+        index: ChunkIndex,
+        /// Edge chunks that extend beyond the dataset are stored unfiltered.
+        unfiltered_partial_chunks: bool,
+    },
 }
 
-fn parse_chunked(input: &mut (impl Read + Seek)) -> Result<DataStorage, Error> {
+fn parse_chunked(
+    input: &mut (impl Read + Seek),
+    context: &FormatContext,
+) -> Result<DataStorage, Error> {
     let dimensions = input.read_u8()? as usize;
-    let address = input.read_u64::<LittleEndian>()?;
+    let address = context.read_offset(input)?;
     let mut chunk_shape = vec![];
     for _ in 0..dimensions {
         chunk_shape.push(input.read_u32::<LittleEndian>()?);
@@ -35,33 +85,145 @@ fn parse_chunked(input: &mut (impl Read + Seek)) -> Result<DataStorage, Error> {
     Ok(DataStorage::Chunked {
         chunk_shape,
         address,
+        index: ChunkIndex::BTreeV1,
+        unfiltered_partial_chunks: false,
     })
 }
 
-fn parse_contiguous(input: &mut (impl Read + Seek)) -> Result<DataStorage, Error> {
+fn parse_chunked_v4(
+    input: &mut (impl Read + Seek),
+    context: &FormatContext,
+) -> Result<DataStorage, Error> {
+    let flags = input.read_u8()?;
+    let dimensions = input.read_u8()?;
+    let encoded_length = input.read_u8()? as usize;
+    if !(1..=8).contains(&encoded_length) {
+        return Err(Error::OxifiveError(format!(
+            "Invalid encoded length of chunk dimensions: {}",
+            encoded_length
+        )));
+    }
+    let mut chunk_shape = vec![];
+    for _ in 0..dimensions {
+        let size = input.read_uint::<LittleEndian>(encoded_length)?;
+        chunk_shape.push(
+            u32::try_from(size).map_err(|_| {
+                Error::OxifiveError(format!("Chunk dimension {} is too large", size))
+            })?,
+        );
+    }
+    let index_type = ChunkIndexType::try_from(input.read_u8()?)?;
+    let index = match index_type {
+        ChunkIndexType::SingleChunk if flags & SINGLE_INDEX_WITH_FILTER != 0 => {
+            let size = context.read_length(input)?;
+            let filter_mask = input.read_u32::<LittleEndian>()?;
+            ChunkIndex::SingleChunk {
+                filtered: Some((size, filter_mask)),
+            }
+        }
+        ChunkIndexType::SingleChunk => ChunkIndex::SingleChunk { filtered: None },
+        ChunkIndexType::Implicit => ChunkIndex::Implicit,
+        ChunkIndexType::FixedArray => ChunkIndex::FixedArray {
+            page_bits: input.read_u8()?,
+        },
+        ChunkIndexType::ExtensibleArray => {
+            // Maximum bits, index elements, minimum pointers, minimum elements
+            // and page bits
+            let mut parameters = [0; 5];
+            input.read_exact(&mut parameters)?;
+            ChunkIndex::Unsupported(index_type)
+        }
+        ChunkIndexType::BTreeV2 => {
+            // Node size, split percent and merge percent
+            let mut parameters = [0; 6];
+            input.read_exact(&mut parameters)?;
+            ChunkIndex::Unsupported(index_type)
+        }
+    };
+    Ok(DataStorage::Chunked {
+        chunk_shape,
+        address: context.read_offset(input)?,
+        index,
+        unfiltered_partial_chunks: flags & DONT_FILTER_PARTIAL_BOUND_CHUNKS != 0,
+    })
+}
+
+fn parse_compact(input: &mut (impl Read + Seek)) -> Result<DataStorage, Error> {
+    let size = input.read_u16::<LittleEndian>()?;
+    let mut data = vec![0; size as usize];
+    input.read_exact(&mut data)?;
+    Ok(DataStorage::Compact { data })
+}
+
+fn parse_contiguous(
+    input: &mut (impl Read + Seek),
+    context: &FormatContext,
+) -> Result<DataStorage, Error> {
     Ok(DataStorage::Contiguous {
-        address: input.read_u64::<LittleEndian>()?,
-        size: input.read_u64::<LittleEndian>()?,
+        address: context.read_offset(input)?,
+        size: context.read_length(input)?,
     })
 }
 
-pub fn parse_data_storage_message(input: &mut Cursor<Vec<u8>>) -> Result<DataStorage, Error> {
-    let version = input.read_u8()?;
-    if version != 3 {
-        return Err(Error::OxifiveError(format!(
-            "Unsupported data storage version {}",
-            version
-        )));
-    }
+/// Parses the version 1 and 2 encodings, in which all layout classes store
+/// their dimensions and compact data has a 32-bit size.
+fn parse_legacy(
+    input: &mut (impl Read + Seek),
+    context: &FormatContext,
+) -> Result<DataStorage, Error> {
+    let dimensions = input.read_u8()?;
     let layout_class = LayoutClass::try_from(input.read_u8()?)?;
-    match layout_class {
-        LayoutClass::Contiguous => parse_contiguous(input),
-        LayoutClass::Chunked => parse_chunked(input),
+    let mut reserved = [0; 5];
+    input.read_exact(&mut reserved)?;
+    let address = match layout_class {
+        LayoutClass::Compact => None,
+        _ => Some(context.read_offset(input)?),
+    };
+    let mut shape = vec![];
+    for _ in 0..dimensions {
+        shape.push(input.read_u32::<LittleEndian>()?);
+    }
+    match (layout_class, address) {
+        (LayoutClass::Contiguous, Some(address)) => Ok(DataStorage::Contiguous {
+            address,
+            // The last dimension is the size of the datatype
+            size: shape.iter().map(|&x| u64::from(x)).product(),
+        }),
+        (LayoutClass::Chunked, Some(address)) => Ok(DataStorage::Chunked {
+            chunk_shape: shape,
+            address,
+            index: ChunkIndex::BTreeV1,
+            unfiltered_partial_chunks: false,
+        }),
+        _ => {
+            let size = input.read_u32::<LittleEndian>()?;
+            let mut data = vec![0; size as usize];
+            input.read_exact(&mut data)?;
+            Ok(DataStorage::Compact { data })
+        }
+    }
+}
+
+pub fn parse_data_storage_message(
+    input: &mut Cursor<Vec<u8>>,
+    context: &FormatContext,
+) -> Result<DataStorage, Error> {
+    let version = input.read_u8()?;
+    match version {
+        1 | 2 => return parse_legacy(input, context),
+        3 | 4 => {}
         _ => {
             return Err(Error::OxifiveError(format!(
-                "Only chunked data storage is supported, found layout_class {:?}",
-                layout_class
-            )));
+                "Unsupported data storage version {}",
+                version
+            )))
         }
     }
+    let layout_class = LayoutClass::try_from(input.read_u8()?)?;
+    match layout_class {
+        LayoutClass::Contiguous => parse_contiguous(input, context),
+        LayoutClass::Chunked if version == 4 => parse_chunked_v4(input, context),
+        LayoutClass::Chunked => parse_chunked(input, context),
+        LayoutClass::Compact => parse_compact(input),
+    }
 }
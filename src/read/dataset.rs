@@ -1,126 +1,923 @@
 use crate::error::Error;
 use crate::read::{
+    chunk_index::{read_chunk_index, Chunk},
+    context::{FormatContext, UNDEFINED_ADDRESS},
+    conversion::{self, ConversionPolicy, Number},
     data_object::DataObject,
     data_storage::DataStorage,
     dataspace::Dataspace,
-    datatype::Datatype,
-    datatype::DatatypeEncoding,
-    filter_pipeline::{FilterPipeline, FilterType},
-    node::{parse_node, BTreeNode},
+    datatype::{decode_string, ByteOrder, Datatype, DatatypeProperties, VariableLengthKind},
+    decode::DecodeContext,
+    filter_pipeline::FilterType,
+    reference::Selection,
 };
 use crate::ReadSeek;
-use ndarray::{Array, ArrayD, Dimension, IxDyn, SliceInfo, SliceInfoElem};
-use num_traits::identities::Zero;
+use ndarray::{
+    Array, Array1, ArrayD, ArrayViewD, Dimension, Ix0, Ix1, IxDyn, SliceInfo, SliceInfoElem,
+};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::{
-    collections::HashMap,
+    convert::TryInto,
     fmt::Debug,
-    io::{Cursor, Read, SeekFrom},
+    io::{Cursor, Read},
+    mem::MaybeUninit,
+    ops::Range,
 };
 
 #[derive(Clone, Debug)]
 pub struct Dataset<R> {
     pub data_object: DataObject,
+    pub context: FormatContext,
     pub input: Arc<Mutex<R>>,
+    /// How values that do not fit the numeric type they are read into are
+    /// handled.
+    pub conversion: ConversionPolicy,
 }
 
-pub trait DatatypeVerifiable {
+pub trait DatatypeVerifiable: Sized {
     fn verify(datatype: &Datatype) -> Result<(), Error>;
+
+    /// Decodes a single element from its bytes in the file, converting from
+    /// the byte order of the datatype to native byte order. Variable-length
+    /// data is read from the global heap of the decode context.
+    fn decode(
+        bytes: &[u8],
+        datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error>;
+
+    /// Decodes a whole buffer of elements at once, for datatypes that are
+    /// stored the way `Self` is laid out in memory. The datatype is checked
+    /// once per read, and other datatypes are decoded element by element.
+    fn buffer_decoder(_datatype: &Datatype) -> Option<BufferDecoder<Self>> {
+        None
+    }
 }
 
-macro_rules! add_verifiable_type {
-    ($rust_type:ty, $encoding:expr, $size:expr) => {
+/// Decodes every element of a buffer into the Rust type read from a dataset.
+pub type BufferDecoder<T> = fn(&[u8]) -> Vec<T>;
+
+/// The byte order of the values of Rust types in memory.
+#[cfg(target_endian = "little")]
+const NATIVE_BYTE_ORDER: ByteOrder = ByteOrder::LittleEndian;
+#[cfg(target_endian = "big")]
+const NATIVE_BYTE_ORDER: ByteOrder = ByteOrder::BigEndian;
+
+macro_rules! add_verifiable_float {
+    (
+        $rust_type:ty,
+        $size:expr,
+        $exponent_size:expr,
+        $mantissa_size:expr,
+        $from_f64:expr,
+        $to_f64:expr,
+        $max:expr
+    ) => {
+        /// Integers and floats of other sizes are converted according to the
+        /// conversion policy of the read.
         impl DatatypeVerifiable for $rust_type {
             fn verify(datatype: &Datatype) -> Result<(), Error> {
-                if datatype.encoding != $encoding || datatype.size != $size {
-                    return Err(Error::OxifiveError(format!(
+                match datatype.properties {
+                    DatatypeProperties::FloatingPoint { .. }
+                    | DatatypeProperties::FixedPoint { .. } => Ok(()),
+                    _ => Err(Error::OxifiveError(format!(
                         "Wrong datatype found for {}: {:?}",
                         stringify!($rust_type),
                         datatype
-                    )));
+                    ))),
                 }
-                Ok(())
+            }
+
+            fn decode(
+                bytes: &[u8],
+                datatype: &Datatype,
+                context: &mut DecodeContext,
+            ) -> Result<Self, Error> {
+                if !datatype.is_ieee_float($exponent_size, $mantissa_size) {
+                    return conversion::to_float(
+                        Number::decode(bytes, datatype)?,
+                        context.conversion(),
+                        stringify!($rust_type),
+                        $from_f64,
+                        $to_f64,
+                        $max,
+                    );
+                }
+                let mut value = [0; $size];
+                value.copy_from_slice(bytes);
+                match datatype.byte_order() {
+                    ByteOrder::LittleEndian => Ok(<$rust_type>::from_le_bytes(value)),
+                    ByteOrder::BigEndian => Ok(<$rust_type>::from_be_bytes(value)),
+                    ByteOrder::Vax => Err(Error::OxifiveError(format!(
+                        "VAX byte order is not supported for {}",
+                        stringify!($rust_type)
+                    ))),
+                }
+            }
+
+            fn buffer_decoder(datatype: &Datatype) -> Option<BufferDecoder<Self>> {
+                if !datatype.is_ieee_float($exponent_size, $mantissa_size)
+                    || datatype.byte_order() != NATIVE_BYTE_ORDER
+                {
+                    return None;
+                }
+                Some(|bytes| {
+                    bytes
+                        .chunks_exact($size)
+                        .map(|bytes| {
+                            let mut value = [0; $size];
+                            value.copy_from_slice(bytes);
+                            <$rust_type>::from_ne_bytes(value)
+                        })
+                        .collect()
+                })
             }
         }
     };
 }
 
-add_verifiable_type!(f32, DatatypeEncoding::FloatingPoint, 4);
-add_verifiable_type!(f64, DatatypeEncoding::FloatingPoint, 8);
-add_verifiable_type!(u8, DatatypeEncoding::FixedPoint, 1);
+macro_rules! add_verifiable_integer {
+    ($rust_type:ty, $size:expr) => {
+        /// Integers of other sizes and signedness and floats are converted
+        /// according to the conversion policy of the read.
+        impl DatatypeVerifiable for $rust_type {
+            fn verify(datatype: &Datatype) -> Result<(), Error> {
+                match &datatype.properties {
+                    DatatypeProperties::FixedPoint { .. }
+                    | DatatypeProperties::FloatingPoint { .. } => Ok(()),
+                    // Bitfields and times are read as integers of the same
+                    // size and either signedness
+                    DatatypeProperties::Bitfield { .. } | DatatypeProperties::Time { .. }
+                        if datatype.size == $size =>
+                    {
+                        Ok(())
+                    }
+                    // Enumerated values are read as their integer values
+                    DatatypeProperties::Enumerated { base_type, .. } => Self::verify(base_type),
+                    _ => Err(Error::OxifiveError(format!(
+                        "Wrong datatype found for {}: {:?}",
+                        stringify!($rust_type),
+                        datatype
+                    ))),
+                }
+            }
+
+            fn decode(
+                bytes: &[u8],
+                datatype: &Datatype,
+                context: &mut DecodeContext,
+            ) -> Result<Self, Error> {
+                match &datatype.properties {
+                    DatatypeProperties::Bitfield { .. } | DatatypeProperties::Time { .. } => {
+                        Ok(datatype.decode_fixed_point(bytes)? as $rust_type)
+                    }
+                    _ => conversion::to_integer(
+                        Number::decode(bytes, datatype)?,
+                        context.conversion(),
+                        stringify!($rust_type),
+                    ),
+                }
+            }
+        }
+    };
+}
+
+/// Decodes the bytes of one element into the Rust type read from a dataset.
+type ElementDecoder<'a, T> = dyn Fn(&[u8], &mut DecodeContext) -> Result<T, Error> + 'a;
+
+/// Finds the bytes of the value to read within the bytes of one element.
+type ElementBytes<'a> = dyn Fn(&[u8]) -> Result<&[u8], Error> + 'a;
+
+/// Decodes the elements of a read one at a time, or a whole buffer at once
+/// when the datatype allows it.
+struct Decoder<'a, T> {
+    element: &'a ElementDecoder<'a, T>,
+    buffer: Option<BufferDecoder<T>>,
+}
+
+impl<'a, T> Decoder<'a, T> {
+    fn elements(
+        &self,
+        bytes: &[u8],
+        datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Vec<T>, Error> {
+        match self.buffer {
+            Some(decode) => Ok(decode(bytes)),
+            None => bytes
+                .chunks_exact(datatype.size as usize)
+                .map(|element| (self.element)(element, context))
+                .collect(),
+        }
+    }
+}
+
+/// Filters skipped for a chunk, which are all of them for edge chunks that
+/// extend beyond the dataset when partial chunks are stored unfiltered.
+fn chunk_filter_mask(
+    chunk: &Chunk,
+    element_shape: &[usize],
+    shape: &[usize],
+    unfiltered_partial_chunks: bool,
+) -> u32 {
+    let is_partial = chunk
+        .offsets
+        .iter()
+        .zip(element_shape)
+        .zip(shape)
+        .any(|((&offset, &chunk_size), &size)| offset as usize + chunk_size > size);
+    if unfiltered_partial_chunks && is_partial {
+        u32::MAX
+    } else {
+        chunk.filter_mask
+    }
+}
+
+/// Whether the chunks hold every element of a dataset of the given shape.
+/// Chunks must start at a multiple of the chunk shape inside the dataset.
+fn chunks_cover(chunks: &[Chunk], element_shape: &[usize], shape: &[usize]) -> Result<bool, Error> {
+    let grid: Vec<usize> = shape
+        .iter()
+        .zip(element_shape)
+        .map(|(&size, &chunk_size)| size.div_ceil(chunk_size))
+        .collect();
+    let mut written = vec![false; grid.iter().product()];
+    for chunk in chunks {
+        let aligned = chunk.offsets.len() == shape.len()
+            && chunk.offsets.iter().zip(element_shape).zip(shape).all(
+                |((&offset, &chunk_size), &size)| {
+                    (offset as usize) < size && (offset as usize).is_multiple_of(chunk_size)
+                },
+            );
+        if !aligned {
+            return Err(Error::OxifiveError(format!(
+                "Chunk at offsets {:?} does not fit the chunk shape {:?} and dataset shape {:?}",
+                chunk.offsets, element_shape, shape
+            )));
+        }
+        let number = chunk.offsets.iter().zip(element_shape).zip(&grid).fold(
+            0,
+            |number, ((&offset, &chunk_size), &count)| {
+                number * count + offset as usize / chunk_size
+            },
+        );
+        written[number] = true;
+    }
+    Ok(written.into_iter().all(|written| written))
+}
+
+/// The array that chunks are decoded into, which starts out holding the fill
+/// value when some elements are in no chunk, and uninitialized otherwise.
+enum ChunkTarget<T, D> {
+    Filled(Array<T, D>),
+    Uninitialized(Array<MaybeUninit<T>, D>),
+}
+
+impl<T: Clone, D: Dimension> ChunkTarget<T, D> {
+    fn new(shape: D, fill_value: Option<T>) -> Self {
+        match fill_value {
+            Some(fill_value) => ChunkTarget::Filled(Array::from_elem(shape, fill_value)),
+            None => ChunkTarget::Uninitialized(Array::uninit(shape)),
+        }
+    }
+
+    /// The array with every element written.
+    ///
+    /// # Safety
+    ///
+    /// Without a fill value, every element must have been written.
+    unsafe fn assume_init(self) -> Array<T, D> {
+        match self {
+            ChunkTarget::Filled(array) => array,
+            ChunkTarget::Uninitialized(array) => array.assume_init(),
+        }
+    }
+}
+
+impl<T: Clone> ChunkTarget<T, IxDyn> {
+    /// Decodes elements into a slice of the array, in row-major order.
+    fn decode_slice<'b, 'c: 'b>(
+        &mut self,
+        slice: SliceInfo<Vec<SliceInfoElem>, IxDyn, IxDyn>,
+        elements: impl Iterator<Item = &'b &'c [u8]>,
+        decode: &ElementDecoder<T>,
+        decode_context: &mut DecodeContext,
+    ) -> Result<(), Error> {
+        match self {
+            ChunkTarget::Filled(array) => {
+                for (element, bytes) in array.slice_mut(slice).iter_mut().zip(elements) {
+                    *element = decode(bytes, decode_context)?;
+                }
+            }
+            ChunkTarget::Uninitialized(array) => {
+                for (element, bytes) in array.slice_mut(slice).iter_mut().zip(elements) {
+                    *element = MaybeUninit::new(decode(bytes, decode_context)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies decoded elements into a slice of the array.
+    fn assign_slice(
+        &mut self,
+        slice: SliceInfo<Vec<SliceInfoElem>, IxDyn, IxDyn>,
+        values: ArrayViewD<T>,
+    ) {
+        match self {
+            ChunkTarget::Filled(array) => values.assign_to(array.slice_mut(slice)),
+            ChunkTarget::Uninitialized(array) => values.assign_to(array.slice_mut(slice)),
+        }
+    }
+}
+
+impl<T: Clone> ChunkTarget<T, Ix1> {
+    fn set(&mut self, index: usize, value: T) {
+        match self {
+            ChunkTarget::Filled(array) => array[index] = value,
+            ChunkTarget::Uninitialized(array) => array[index] = MaybeUninit::new(value),
+        }
+    }
+}
+
+/// Filters only apply to chunked storage, so other layouts must not have any.
+fn verify_unfiltered(data_object: &DataObject) -> Result<(), Error> {
+    if data_object.filter_pipelines.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::OxifiveError(format!(
+            "Filters are only supported for chunked data, found {:?}",
+            data_object.filter_pipelines
+        )))
+    }
+}
+
+/// Null dataspaces have no elements, and are read as an empty array with as
+/// many dimensions as requested.
+fn read_null<T, D: Dimension>() -> Result<Array<T, D>, Error> {
+    match D::NDIM.unwrap_or(1) {
+        0 => Err(Error::OxifiveError(
+            "Cannot read a null dataspace into a zero-dimensional array".to_string(),
+        )),
+        ndim => Ok(ArrayD::from_shape_vec(vec![0; ndim], vec![])?.into_dimensionality()?),
+    }
+}
+
+add_verifiable_float!(
+    f32,
+    4,
+    8,
+    23,
+    |value| value as f32,
+    f64::from,
+    f64::from(f32::MAX)
+);
+add_verifiable_float!(f64, 8, 11, 52, |value| value, |value| value, f64::MAX);
+#[cfg(feature = "half")]
+add_verifiable_float!(
+    half::f16,
+    2,
+    5,
+    10,
+    half::f16::from_f64,
+    half::f16::to_f64,
+    half::f16::MAX.to_f64()
+);
+add_verifiable_integer!(u8, 1);
+add_verifiable_integer!(u16, 2);
+add_verifiable_integer!(u32, 4);
+add_verifiable_integer!(u64, 8);
+add_verifiable_integer!(i8, 1);
+add_verifiable_integer!(i16, 2);
+add_verifiable_integer!(i32, 4);
+add_verifiable_integer!(i64, 8);
+
+/// Whether an enumerated datatype is h5py's boolean type, an enum of FALSE and
+/// TRUE over a one-byte integer.
+fn is_boolean_enum(datatype: &Datatype) -> bool {
+    match &datatype.properties {
+        DatatypeProperties::Enumerated { base_type, members } => {
+            base_type.size == 1
+                && members.len() == 2
+                && members
+                    .iter()
+                    .zip(&["FALSE", "TRUE"])
+                    .zip(&[0, 1])
+                    .all(|((member, &name), &value)| member.name == name && member.value == [value])
+        }
+        _ => false,
+    }
+}
+
+/// Booleans are stored as one-byte integers, where any non-zero value is true,
+/// or as h5py's boolean enum.
+impl DatatypeVerifiable for bool {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        match datatype.properties {
+            DatatypeProperties::FixedPoint { .. } if datatype.size == 1 => Ok(()),
+            DatatypeProperties::Enumerated { .. } if is_boolean_enum(datatype) => Ok(()),
+            _ => Err(Error::OxifiveError(format!(
+                "Wrong datatype found for bool: {:?}",
+                datatype
+            ))),
+        }
+    }
+
+    fn decode(
+        bytes: &[u8],
+        datatype: &Datatype,
+        _context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        match &datatype.properties {
+            DatatypeProperties::Enumerated { base_type, .. } => {
+                Ok(base_type.decode_fixed_point(bytes)? != 0)
+            }
+            _ => Ok(datatype.decode_fixed_point(bytes)? != 0),
+        }
+    }
+}
+
+/// Fixed-length and variable-length strings, with the padding removed, and the
+/// member names of enumerated values.
+impl DatatypeVerifiable for String {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        match datatype.properties {
+            DatatypeProperties::String { .. }
+            | DatatypeProperties::Enumerated { .. }
+            | DatatypeProperties::VariableLength {
+                kind: VariableLengthKind::String,
+                ..
+            } => Ok(()),
+            _ => Err(Error::OxifiveError(format!(
+                "Wrong datatype found for String: {:?}",
+                datatype
+            ))),
+        }
+    }
+
+    fn decode(
+        bytes: &[u8],
+        datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        match datatype.properties {
+            DatatypeProperties::String {
+                padding,
+                character_set,
+            } => decode_string(bytes, padding, character_set),
+            DatatypeProperties::VariableLength {
+                padding,
+                character_set,
+                ..
+            } => decode_string(
+                context.heap().variable_length_data(bytes)?.1,
+                padding,
+                character_set,
+            ),
+            DatatypeProperties::Enumerated { .. } => {
+                Ok(datatype.enum_member_name(bytes)?.to_string())
+            }
+            _ => Err(Error::OxifiveError(format!(
+                "Expected a string datatype, found {:?}",
+                datatype
+            ))),
+        }
+    }
+}
+
+/// Array datatypes with `N` elements in total, read in row-major order.
+impl<T: DatatypeVerifiable, const N: usize> DatatypeVerifiable for [T; N] {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        match &datatype.properties {
+            DatatypeProperties::Array {
+                dimensions,
+                base_type,
+            } if dimensions.iter().product::<u32>() as usize == N => T::verify(base_type),
+            _ => Err(Error::OxifiveError(format!(
+                "Wrong datatype found for an array of {} elements: {:?}",
+                N, datatype
+            ))),
+        }
+    }
+
+    fn decode(
+        bytes: &[u8],
+        datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        let (base_type, _) = datatype.array_element();
+        let values = bytes
+            .chunks_exact(base_type.size as usize)
+            .map(|value| T::decode(value, base_type, context))
+            .collect::<Result<Vec<T>, Error>>()?;
+        let count = values.len();
+        values.try_into().map_err(|_| {
+            Error::OxifiveError(format!(
+                "Expected an array of {} elements, found {}",
+                N, count
+            ))
+        })
+    }
+}
+
+/// Variable-length sequences, read from the global heap.
+impl<T: DatatypeVerifiable> DatatypeVerifiable for Vec<T> {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        match &datatype.properties {
+            DatatypeProperties::VariableLength {
+                kind: VariableLengthKind::Sequence,
+                base_type,
+                ..
+            } => T::verify(base_type),
+            _ => Err(Error::OxifiveError(format!(
+                "Wrong datatype found for a variable-length sequence: {:?}",
+                datatype
+            ))),
+        }
+    }
+
+    fn decode(
+        bytes: &[u8],
+        datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        let base_type = match &datatype.properties {
+            DatatypeProperties::VariableLength { base_type, .. } => base_type,
+            _ => {
+                return Err(Error::OxifiveError(format!(
+                    "Expected a variable-length datatype, found {:?}",
+                    datatype
+                )))
+            }
+        };
+        let (length, data) = context.heap().variable_length_data(bytes)?;
+        let size = length as usize * base_type.size as usize;
+        let data = data
+            .get(..size)
+            .ok_or_else(|| {
+                Error::OxifiveError(format!(
+                    "Variable-length sequence of {} elements exceeds its heap object of {} bytes",
+                    length,
+                    data.len()
+                ))
+            })?
+            .to_vec();
+        data.chunks_exact(base_type.size as usize)
+            .map(|value| T::decode(value, base_type, context))
+            .collect()
+    }
+}
+
+/// Finds the real and imaginary members of a compound datatype holding complex
+/// numbers, named `r` and `i` by h5py and PyTables or `real` and `imag` by
+/// Octave.
+#[cfg(feature = "num-complex")]
+fn complex_members(
+    datatype: &Datatype,
+) -> Result<
+    (
+        &crate::read::datatype::CompoundMember,
+        &crate::read::datatype::CompoundMember,
+    ),
+    Error,
+> {
+    if let DatatypeProperties::Compound { members } = &datatype.properties {
+        if let [real, imaginary] = &members[..] {
+            match (&real.name[..], &imaginary.name[..]) {
+                ("r", "i") | ("real", "imag") => return Ok((real, imaginary)),
+                _ => {}
+            }
+        }
+    }
+    Err(Error::OxifiveError(format!(
+        "Wrong datatype found for a complex number: {:?}",
+        datatype
+    )))
+}
+
+#[cfg(feature = "num-complex")]
+impl<T: DatatypeVerifiable> DatatypeVerifiable for num_complex::Complex<T> {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        let (real, imaginary) = complex_members(datatype)?;
+        real.verify::<T>()?;
+        imaginary.verify::<T>()
+    }
+
+    fn decode(
+        bytes: &[u8],
+        datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        let (real, imaginary) = complex_members(datatype)?;
+        Ok(num_complex::Complex::new(
+            T::decode(real.bytes(bytes)?, &real.datatype, context)?,
+            T::decode(imaginary.bytes(bytes)?, &imaginary.datatype, context)?,
+        ))
+    }
+}
 
 impl<R: ReadSeek> Dataset<R> {
     pub fn shape(&self) -> Vec<u64> {
-        self.data_object.dataspaces[0].shape.clone()
+        self.dataspace().shape().to_vec()
+    }
+
+    /// The maximum size of each dimension, with `None` for unlimited
+    /// dimensions, along which the dataset can be extended.
+    pub fn maxshape(&self) -> Vec<Option<u64>> {
+        self.dataspace().max_shape().to_vec()
+    }
+
+    pub fn dataspace(&self) -> Dataspace {
+        self.data_object.dataspaces[0].clone()
     }
 
     pub fn datatype(&self) -> Datatype {
         self.data_object.datatypes[0].clone()
     }
 
+    /// Sets how values that do not fit the numeric type they are read into
+    /// are handled.
+    pub fn with_conversion(mut self, conversion: ConversionPolicy) -> Self {
+        self.conversion = conversion;
+        self
+    }
+
     pub fn read<T, D>(&self) -> Result<Array<T, D>, Error>
     where
-        T: Clone + Copy + Debug + DatatypeVerifiable + Zero,
+        T: Clone + Debug + DatatypeVerifiable,
+        D: Dimension,
+    {
+        if self.dataspace() == Dataspace::Null {
+            return read_null();
+        }
+        let datatype = self.datatype();
+        if let Some(buffer) = T::buffer_decoder(&datatype) {
+            return self.read_decoded(&Decoder {
+                element: &|bytes, context| T::decode(bytes, &datatype, context),
+                buffer: Some(buffer),
+            });
+        }
+        let array = self.read_elements(&datatype, &|bytes| Ok(bytes))?;
+        Ok(array.into_dimensionality()?)
+    }
+
+    /// Reads a single member of a compound dataset.
+    pub fn read_field<T>(&self, name: &str) -> Result<ArrayD<T>, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+    {
+        let datatype = self.datatype();
+        let member = datatype.compound_member(name)?;
+        self.read_elements(&member.datatype, &|bytes| member.bytes(bytes))
+    }
+
+    /// Reads several members of a compound dataset of the same Rust type,
+    /// returning one array per member in the order of the names.
+    pub fn read_fields<T>(&self, names: &[&str]) -> Result<Vec<ArrayD<T>>, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+    {
+        let datatype = self.datatype();
+        let members = names
+            .iter()
+            .map(|name| datatype.compound_member(name))
+            .collect::<Result<Vec<_>, Error>>()?;
+        for member in &members {
+            member.verify::<T>()?;
+        }
+        let rows: ArrayD<Vec<T>> = self.read_with(&|bytes, context| {
+            members
+                .iter()
+                .map(|member| T::decode(member.bytes(bytes)?, &member.datatype, context))
+                .collect()
+        })?;
+        Ok((0..members.len())
+            .map(|index| rows.map(|row| row[index].clone()))
+            .collect())
+    }
+
+    /// Reads the values of `datatype`, which `bytes` finds within each dataset
+    /// element. Array datatypes that cannot be read as `T` as a whole are read
+    /// element by element, with the array dimensions appended to the shape.
+    fn read_elements<T>(
+        &self,
+        datatype: &Datatype,
+        bytes: &ElementBytes,
+    ) -> Result<ArrayD<T>, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+    {
+        let (base_type, dimensions) = datatype.array_element();
+        match T::verify(datatype) {
+            Ok(()) => {
+                return self
+                    .read_with(&|element, context| T::decode(bytes(element)?, datatype, context))
+            }
+            Err(error) if dimensions.is_empty() => return Err(error),
+            Err(_) => {}
+        }
+        T::verify(base_type)?;
+        let elements: ArrayD<Vec<T>> = self.read_with(&|element, context| {
+            bytes(element)?
+                .chunks_exact(base_type.size as usize)
+                .map(|value| T::decode(value, base_type, context))
+                .collect()
+        })?;
+        let mut shape = elements.shape().to_vec();
+        shape.extend(dimensions.iter().map(|&dimension| dimension as usize));
+        Ok(ArrayD::from_shape_vec(
+            shape,
+            elements.into_iter().flatten().collect(),
+        )?)
+    }
+
+    fn read_with<T, D>(&self, decode: &ElementDecoder<T>) -> Result<Array<T, D>, Error>
+    where
+        T: Clone,
+        D: Dimension,
+    {
+        self.read_decoded(&Decoder {
+            element: decode,
+            buffer: None,
+        })
+    }
+
+    fn read_decoded<T, D>(&self, decoder: &Decoder<T>) -> Result<Array<T, D>, Error>
+    where
+        T: Clone,
         D: Dimension,
     {
         let data_object = &self.data_object;
         let datatype = data_object.datatypes[0].clone();
         log::info!("Datatype {:#?}", datatype);
         let dataspace = data_object.dataspaces[0].clone();
-        log::info!("Shape {:#?}", dataspace.shape);
+        log::info!("Dataspace {:#?}", dataspace);
+        if dataspace == Dataspace::Null {
+            return read_null();
+        }
         let data = data_object.data[0].clone();
         match data {
             DataStorage::Chunked {
                 chunk_shape,
                 address,
-            } => self.read_chunked(
-                &chunk_shape,
-                address,
-                &datatype,
-                &dataspace,
-                &data_object.filter_pipelines,
-            ),
+                index,
+                unfiltered_partial_chunks,
+            } => {
+                let chunks = read_chunk_index(
+                    &mut *self.input.lock().unwrap(),
+                    &self.context,
+                    &index,
+                    address,
+                    &chunk_shape,
+                    dataspace.shape(),
+                    dataspace.max_shape(),
+                )?;
+                self.read_chunked(
+                    &chunk_shape,
+                    &chunks,
+                    unfiltered_partial_chunks,
+                    &datatype,
+                    &dataspace,
+                    decoder,
+                )
+            }
             DataStorage::Contiguous { address, size } => {
-                assert!(data_object.filter_pipelines.is_empty());
-                self.read_contiguous(address, size, &datatype, &dataspace)
+                verify_unfiltered(data_object)?;
+                self.read_contiguous(address, size, &datatype, &dataspace, decoder)
+            }
+            DataStorage::Compact { data } => {
+                verify_unfiltered(data_object)?;
+                self.read_compact(&data, &datatype, &dataspace, decoder)
             }
         }
     }
 
+    /// Reads the single element of a dataset with a scalar dataspace.
+    pub fn read_scalar<T>(&self) -> Result<T, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+    {
+        match self.dataspace() {
+            Dataspace::Scalar => Ok(self.read::<T, Ix0>()?.into_scalar()),
+            dataspace => Err(Error::OxifiveError(format!(
+                "Expected a scalar dataspace, found {:?}",
+                dataspace
+            ))),
+        }
+    }
+
+    /// Reads a dataset of fixed-length or variable-length strings.
+    pub fn read_strings<D: Dimension>(&self) -> Result<Array<String, D>, Error> {
+        self.read::<String, D>()
+    }
+
+    /// Reads the elements of a selection, such as the region of a region
+    /// reference, in the order of `Selection::ranges`. Only the selected
+    /// elements are read, and chunks without any of them are skipped.
+    pub fn read_selection<T>(&self, selection: &Selection) -> Result<Array1<T>, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+    {
+        let datatype = self.datatype();
+        T::verify(&datatype)?;
+        let dataspace = self.dataspace();
+        let ranges = selection.ranges(dataspace.shape())?;
+        if dataspace == Dataspace::Null && !ranges.is_empty() {
+            return Err(Error::OxifiveError(
+                "Cannot select elements of a null dataspace".to_string(),
+            ));
+        }
+        self.read_ranges(
+            &ranges,
+            &datatype,
+            &Decoder {
+                element: &|bytes, context| T::decode(bytes, &datatype, context),
+                buffer: T::buffer_decoder(&datatype),
+            },
+        )
+    }
+
+    /// Reads a dataset of opaque elements as their raw bytes, together with the
+    /// tag that describes them.
+    pub fn read_opaque<D: Dimension>(&self) -> Result<(Array<Vec<u8>, D>, String), Error> {
+        let datatype = self.datatype();
+        let tag = match datatype.properties {
+            DatatypeProperties::Opaque { tag } => tag,
+            _ => {
+                return Err(Error::OxifiveError(format!(
+                    "Expected an opaque datatype, found {:?}",
+                    datatype
+                )))
+            }
+        };
+        let array = self.read_with(&|bytes, _context| Ok(bytes.to_vec()))?;
+        Ok((array, tag))
+    }
+
+    /// Reads a dataset of variable-length sequences, such as ragged rows of
+    /// numbers, with one vector per element.
+    pub fn read_vlen<T, D>(&self) -> Result<Array<Vec<T>, D>, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+        D: Dimension,
+    {
+        self.read::<Vec<T>, D>()
+    }
+
     fn read_contiguous<T, D>(
         &self,
         address: u64,
         size: u64,
         datatype: &Datatype,
         dataspace: &Dataspace,
+        decoder: &Decoder<T>,
     ) -> Result<Array<T, D>, Error>
     where
-        T: Clone + Copy + Debug + DatatypeVerifiable + Zero,
+        T: Clone,
         D: Dimension,
     {
-        T::verify(datatype)?;
-
+        // Storage of datasets that were never written is not allocated, and all
+        // elements hold the fill value
+        if address == UNDEFINED_ADDRESS {
+            let shape: Vec<usize> = dataspace.shape().iter().map(|&x| x as usize).collect();
+            let mut input = self.input.lock().unwrap();
+            let mut decode_context = DecodeContext::new(&mut *input, self.context, self.conversion);
+            let fill_value =
+                self.decode_fill_value(datatype, decoder.element, &mut decode_context)?;
+            return Ok(ArrayD::from_elem(shape, fill_value).into_dimensionality()?);
+        }
         let mut buffer = vec![0; size as usize];
+        {
+            let mut input = self.input.lock().unwrap();
+            self.context.seek(&mut *input, address)?;
+            input.read_exact(&mut buffer)?;
+        }
+        self.read_compact(&buffer, datatype, dataspace, decoder)
+    }
+
+    fn read_compact<T, D>(
+        &self,
+        data: &[u8],
+        datatype: &Datatype,
+        dataspace: &Dataspace,
+        decoder: &Decoder<T>,
+    ) -> Result<Array<T, D>, Error>
+    where
+        T: Clone,
+        D: Dimension,
+    {
         let mut input = self.input.lock().unwrap();
-        input.seek(SeekFrom::Start(address))?;
-        input.read_exact(&mut buffer)?;
-
-        let mut clone = std::mem::ManuallyDrop::new(buffer);
-        let vector = unsafe {
-            Vec::from_raw_parts(
-                clone.as_mut_ptr() as *mut T,
-                clone.len() / std::mem::size_of::<T>(),
-                clone.capacity() / std::mem::size_of::<T>(),
-            )
-        };
+        let mut decode_context = DecodeContext::new(&mut *input, self.context, self.conversion);
+        let vector = decoder.elements(data, datatype, &mut decode_context)?;
 
         log::info!(
-            "Contiguous len {:?} and shape {:?}",
+            "Raw data len {:?} and shape {:?}",
             vector.len(),
-            dataspace.shape
+            dataspace.shape()
         );
 
-        let shape: Vec<usize> = dataspace.shape.iter().map(|&x| x as usize).collect();
+        let shape: Vec<usize> = dataspace.shape().iter().map(|&x| x as usize).collect();
         let array = ArrayD::from_shape_vec(shape, vector)?;
         Ok(array.into_dimensionality()?)
     }
@@ -128,154 +925,342 @@ impl<R: ReadSeek> Dataset<R> {
     fn read_chunked<T, D>(
         &self,
         chunk_shape: &[u32],
-        address: u64,
+        chunks: &[Chunk],
+        unfiltered_partial_chunks: bool,
         datatype: &Datatype,
         dataspace: &Dataspace,
-        filter_pipelines: &[FilterPipeline],
+        decoder: &Decoder<T>,
     ) -> Result<Array<T, D>, Error>
     where
-        T: Clone + Copy + Debug + DatatypeVerifiable + Zero,
+        T: Clone,
         D: Dimension,
     {
-        T::verify(datatype)?;
-
         log::info!("Data chunk shape {:#?}", chunk_shape);
+        log::info!("Chunks {:#?}", chunks);
 
-        let dimensions = chunk_shape.len();
+        let filter_pipelines = &self.data_object.filter_pipelines;
         let input = &mut *self.input.lock().unwrap();
 
-        let root_node = parse_node(input, address, dimensions)?;
-
-        let mut nodes = HashMap::<u8, Vec<BTreeNode>>::new();
-        let mut node_level = root_node.node_level;
-        nodes.insert(node_level, vec![root_node]);
-        while node_level != 0 {
-            let mut next_nodes = vec![];
-            for parent_node in &nodes[&node_level] {
-                for key in &parent_node.keys {
-                    let address = key.chunk_address;
-                    next_nodes.push(parse_node(input, address, dimensions)?);
-                }
-            }
-            let next_node_level = next_nodes[0].node_level;
-            nodes.insert(next_node_level, next_nodes);
-            node_level = next_node_level;
-        }
-
-        log::info!("Nodes {:#?}", nodes);
-
-        // TODO make into u64 for safety
-        let element_count: usize = chunk_shape.iter().product::<u32>() as usize;
+        // The last chunk dimension is the size of the datatype
+        let element_shape: Vec<usize> = chunk_shape[..chunk_shape.len() - 1]
+            .iter()
+            .map(|&x| x as usize)
+            .collect();
+        let element_count: usize = element_shape.iter().product();
 
         log::info!("Element count {}", element_count);
 
-        let shape: Vec<usize> = dataspace.shape.iter().map(|&x| x as usize).collect();
-        // TODO verify D here
-        //assert!(shape.len() == D::dimensions());
-        let mut array = ArrayD::<T>::zeros(shape);
+        let shape: Vec<usize> = dataspace.shape().iter().map(|&x| x as usize).collect();
         let item_size = datatype.size as usize;
+        let mut decode_context = DecodeContext::new(input, self.context, self.conversion);
+        // The fill value is only decoded for elements that no chunk holds
+        let fill_value = if chunks_cover(chunks, &element_shape, &shape)? {
+            None
+        } else {
+            Some(self.decode_fill_value(datatype, decoder.element, &mut decode_context)?)
+        };
+        let mut array = ChunkTarget::new(IxDyn(&shape), fill_value);
         let chunk_buffer_size = element_count * item_size;
 
         for filter in filter_pipelines {
             log::info!("Found filter {:#?}", filter);
         }
-        for node in &nodes[&0] {
-            for node_key in &node.keys {
-                let address = node_key.chunk_address;
-                input.seek(SeekFrom::Start(address))?;
-                let chunk_array = {
-                    let byte_buffer = {
-                        if filter_pipelines.is_empty() {
-                            // TODO compare with chunk_size
-                            // TODO might be untested
-                            let mut buffer = vec![0; chunk_buffer_size];
-                            input.read_exact(&mut buffer)?;
-                            buffer
-                        } else {
-                            let mut buffer = vec![0; node_key.chunk_size as usize];
-                            input.read_exact(&mut buffer)?;
-                            if node_key.filter_mask != 0 {
-                                return Err(Error::OxifiveError(format!(
-                                    "Filter masks are not yet supported: {}",
-                                    node_key.filter_mask
-                                )));
-                            }
-                            for filter in filter_pipelines.iter().rev() {
-                                log::info!("Running filter {:#?}", filter);
-                                match filter.filter_type {
-                                    FilterType::Shuffle => {
-                                        // TODO consider using itertools::interleave
-                                        let buffer_size = buffer.len();
-                                        let mut unshuffled_buffer = vec![0; buffer_size];
-                                        let item_count = buffer_size / item_size;
-                                        for item_index in 0..item_count {
-                                            for byte_index in 0..item_size {
-                                                let unshuffled_index =
-                                                    item_index * item_size + byte_index;
-                                                let shuffled_index =
-                                                    byte_index * item_count + item_index;
-                                                unshuffled_buffer[unshuffled_index] =
-                                                    buffer[shuffled_index];
-                                            }
-                                        }
-                                        buffer.copy_from_slice(&unshuffled_buffer[..]);
-                                    }
-                                    FilterType::GzipDeflate => {
-                                        let mut reader = Cursor::new(&buffer);
-                                        let mut decoder =
-                                            flate2::read::ZlibDecoder::new(&mut reader);
-                                        let mut decompressed = vec![];
-                                        decoder.read_to_end(&mut decompressed)?;
-                                        log::info!("Decompressed into {}", decompressed.len());
-                                        //let decompressed = miniz_oxide::inflate::decompress_to_vec(&buffer)?;
-                                        buffer.resize(decompressed.len(), 0);
-                                        buffer.clone_from_slice(&decompressed[..]);
-                                    }
-                                    _ => {
-                                        return Err(Error::OxifiveError(format!(
-                                            "Unsupported filter type: {:#?}",
-                                            filter
-                                        )));
-                                    }
-                                }
+        for chunk in chunks {
+            let filter_mask =
+                chunk_filter_mask(chunk, &element_shape, &shape, unfiltered_partial_chunks);
+            let byte_buffer = self.read_chunk(
+                decode_context.heap().input(),
+                chunk,
+                filter_mask,
+                item_size,
+                chunk_buffer_size,
+            )?;
+            let (target, source): (Vec<_>, Vec<_>) = chunk
+                .offsets
+                .iter()
+                .zip(&element_shape)
+                .zip(&shape)
+                .map(|((&offset, &chunk_size), &size)| {
+                    let start = offset as usize;
+                    let end = (start + chunk_size).min(size);
+                    (
+                        SliceInfoElem::from(start..end),
+                        SliceInfoElem::from(0..end - start),
+                    )
+                })
+                .unzip();
+            let target: SliceInfo<_, IxDyn, IxDyn> = unsafe { SliceInfo::new(target)? };
+            let source: SliceInfo<_, IxDyn, IxDyn> = unsafe { SliceInfo::new(source)? };
+            log::info!(
+                "Reading {} elements into shape {:?}",
+                byte_buffer.len() / item_size,
+                element_shape
+            );
+            match decoder.buffer {
+                Some(decode) => {
+                    let values =
+                        ArrayD::from_shape_vec(element_shape.clone(), decode(&byte_buffer))?;
+                    array.assign_slice(target, values.slice(source));
+                }
+                None => {
+                    let elements: Vec<&[u8]> = byte_buffer.chunks_exact(item_size).collect();
+                    let elements = ArrayD::from_shape_vec(element_shape.clone(), elements)?;
+                    // Elements of edge chunks outside the dataset are never
+                    // decoded
+                    array.decode_slice(
+                        target,
+                        elements.slice(source).iter(),
+                        decoder.element,
+                        &mut decode_context,
+                    )?;
+                }
+            }
+        }
+        // Without a fill value, the chunks cover every element
+        let array = unsafe { array.assume_init() };
+        Ok(array.into_dimensionality()?)
+    }
+
+    /// Reads the bytes of a chunk, undoing the filters that were applied to
+    /// it in reverse order of the pipeline.
+    fn read_chunk(
+        &self,
+        input: &mut dyn ReadSeek,
+        chunk: &Chunk,
+        filter_mask: u32,
+        item_size: usize,
+        buffer_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let filter_pipelines = &self.data_object.filter_pipelines;
+        self.context.seek(input, chunk.address)?;
+        let buffer = if filter_pipelines.is_empty() {
+            let mut buffer = vec![0; buffer_size];
+            input.read_exact(&mut buffer)?;
+            buffer
+        } else {
+            let mut buffer = vec![0; chunk.size as usize];
+            input.read_exact(&mut buffer)?;
+            for (index, filter) in filter_pipelines.iter().enumerate().rev() {
+                if filter_mask & (1 << index) != 0 {
+                    log::info!("Skipping masked filter {:#?}", filter);
+                    continue;
+                }
+                log::info!("Running filter {:#?}", filter);
+                match filter.filter_type {
+                    FilterType::Shuffle => {
+                        // TODO consider using itertools::interleave
+                        let buffer_size = buffer.len();
+                        let mut unshuffled_buffer = vec![0; buffer_size];
+                        let item_count = buffer_size / item_size;
+                        for item_index in 0..item_count {
+                            for byte_index in 0..item_size {
+                                let unshuffled_index = item_index * item_size + byte_index;
+                                let shuffled_index = byte_index * item_count + item_index;
+                                unshuffled_buffer[unshuffled_index] = buffer[shuffled_index];
                             }
-                            buffer
                         }
-                    };
-                    let mut clone = std::mem::ManuallyDrop::new(byte_buffer);
-                    let chunk_vector = unsafe {
-                        Vec::from_raw_parts(
-                            clone.as_mut_ptr() as *mut T,
-                            clone.len() / std::mem::size_of::<T>(),
-                            clone.capacity() / std::mem::size_of::<T>(),
-                        )
-                    };
-                    let shape: Vec<usize> = chunk_shape[..chunk_shape.len() - 1]
-                        .iter()
-                        .map(|&x| x as usize)
-                        .collect();
-                    log::info!(
-                        "Reading vector of length {} into shape {:?}",
-                        chunk_vector.len(),
-                        shape
-                    );
-                    ArrayD::from_shape_vec(shape, chunk_vector)?
-                };
-                let slice_elements: Vec<SliceInfoElem> = node_key.chunk_offsets
-                    [..node_key.chunk_offsets.len() - 1]
+                        buffer.copy_from_slice(&unshuffled_buffer[..]);
+                    }
+                    FilterType::GzipDeflate => {
+                        let mut reader = Cursor::new(&buffer);
+                        let mut decoder = flate2::read::ZlibDecoder::new(&mut reader);
+                        let mut decompressed = vec![];
+                        decoder.read_to_end(&mut decompressed)?;
+                        log::info!("Decompressed into {}", decompressed.len());
+                        buffer = decompressed;
+                    }
+                    _ => {
+                        return Err(Error::OxifiveError(format!(
+                            "Unsupported filter type: {:#?}",
+                            filter
+                        )));
+                    }
+                }
+            }
+            buffer
+        };
+        Ok(buffer)
+    }
+
+    /// Reads the elements in ranges of row-major indices, which must be inside
+    /// the dataset. Contiguous data is read one range at a time, and only the
+    /// chunks that hold a selected element are read.
+    fn read_ranges<T: Clone>(
+        &self,
+        ranges: &[Range<u64>],
+        datatype: &Datatype,
+        decoder: &Decoder<T>,
+    ) -> Result<Array1<T>, Error> {
+        let data_object = &self.data_object;
+        let shape = data_object.dataspaces[0].shape();
+        let item_size = datatype.size as usize;
+        let count: u64 = ranges.iter().map(|range| range.end - range.start).sum();
+        if count == 0 {
+            return Ok(Array1::from(vec![]));
+        }
+        let mut input = self.input.lock().unwrap();
+        let mut decode_context = DecodeContext::new(&mut *input, self.context, self.conversion);
+        match &data_object.data[0] {
+            DataStorage::Compact { data } => {
+                verify_unfiltered(data_object)?;
+                let mut values = Vec::with_capacity(count as usize);
+                for range in ranges {
+                    let bytes = data
+                        .get(range.start as usize * item_size..range.end as usize * item_size)
+                        .ok_or_else(|| {
+                            Error::OxifiveError(format!(
+                                "Compact data of {} bytes does not hold elements {:?}",
+                                data.len(),
+                                range
+                            ))
+                        })?;
+                    values.extend(decoder.elements(bytes, datatype, &mut decode_context)?);
+                }
+                Ok(Array1::from(values))
+            }
+            DataStorage::Contiguous { address, .. } => {
+                verify_unfiltered(data_object)?;
+                if *address == UNDEFINED_ADDRESS {
+                    let fill_value =
+                        self.decode_fill_value(datatype, decoder.element, &mut decode_context)?;
+                    return Ok(Array1::from_elem(count as usize, fill_value));
+                }
+                let mut values = Vec::with_capacity(count as usize);
+                for range in ranges {
+                    let mut buffer = vec![0; (range.end - range.start) as usize * item_size];
+                    let input = decode_context.heap().input();
+                    self.context
+                        .seek(input, address + range.start * item_size as u64)?;
+                    input.read_exact(&mut buffer)?;
+                    values.extend(decoder.elements(&buffer, datatype, &mut decode_context)?);
+                }
+                Ok(Array1::from(values))
+            }
+            DataStorage::Chunked {
+                chunk_shape,
+                address,
+                index,
+                unfiltered_partial_chunks,
+            } => {
+                let chunks = read_chunk_index(
+                    decode_context.heap().input(),
+                    &self.context,
+                    index,
+                    *address,
+                    chunk_shape,
+                    shape,
+                    data_object.dataspaces[0].max_shape(),
+                )?;
+                // The last chunk dimension is the size of the datatype
+                let element_shape: Vec<u64> = chunk_shape[..chunk_shape.len() - 1]
                     .iter()
-                    .zip(chunk_shape[..chunk_shape.len() - 1].iter())
-                    .map(|(&offset, &shape)| SliceInfoElem::Slice {
-                        start: offset as isize,
-                        end: Some(offset as isize + shape as isize),
-                        step: 1,
+                    .map(|&x| u64::from(x))
+                    .collect();
+                let grid: Vec<u64> = shape
+                    .iter()
+                    .zip(&element_shape)
+                    .map(|(&size, &chunk_size)| size.div_ceil(chunk_size))
+                    .collect();
+                // Selected elements are grouped by the row-major number of
+                // their chunk, with their positions in the selection and in
+                // the chunk
+                let mut selected = HashMap::<u64, Vec<(usize, u64)>>::new();
+                let mut coordinates = vec![0; shape.len()];
+                let mut position = 0;
+                for range in ranges {
+                    for index in range.clone() {
+                        let mut rest = index;
+                        for dimension in (0..shape.len()).rev() {
+                            coordinates[dimension] = rest % shape[dimension];
+                            rest /= shape[dimension];
+                        }
+                        let (number, within) =
+                            (0..shape.len()).fold((0, 0), |(number, within), dimension| {
+                                let chunk_size = element_shape[dimension];
+                                (
+                                    number * grid[dimension] + coordinates[dimension] / chunk_size,
+                                    within * chunk_size + coordinates[dimension] % chunk_size,
+                                )
+                            });
+                        selected.entry(number).or_default().push((position, within));
+                        position += 1;
+                    }
+                }
+                let element_sizes: Vec<usize> = element_shape.iter().map(|&x| x as usize).collect();
+                let sizes: Vec<usize> = shape.iter().map(|&x| x as usize).collect();
+                let buffer_size = element_sizes.iter().product::<usize>() * item_size;
+                let chunks: HashMap<u64, &Chunk> = chunks
+                    .iter()
+                    .map(|chunk| {
+                        let number = chunk.offsets.iter().zip(&element_shape).zip(&grid).fold(
+                            0,
+                            |number, ((&offset, &chunk_size), &count)| {
+                                number * count + offset / chunk_size
+                            },
+                        );
+                        (number, chunk)
                     })
                     .collect();
-                let slice: SliceInfo<_, IxDyn, IxDyn> = unsafe { SliceInfo::new(slice_elements)? };
-                array.slice_mut(slice).assign(&chunk_array);
+                // The fill value is only decoded for elements that no chunk holds
+                let fill_value = if selected.keys().all(|number| chunks.contains_key(number)) {
+                    None
+                } else {
+                    Some(self.decode_fill_value(datatype, decoder.element, &mut decode_context)?)
+                };
+                let mut values = ChunkTarget::new(Ix1(position), fill_value);
+                for (number, elements) in &selected {
+                    let chunk = match chunks.get(number) {
+                        Some(chunk) => chunk,
+                        None => continue,
+                    };
+                    let filter_mask = chunk_filter_mask(
+                        chunk,
+                        &element_sizes,
+                        &sizes,
+                        *unfiltered_partial_chunks,
+                    );
+                    let buffer = self.read_chunk(
+                        decode_context.heap().input(),
+                        chunk,
+                        filter_mask,
+                        item_size,
+                        buffer_size,
+                    )?;
+                    for &(position, within) in elements {
+                        let start = within as usize * item_size;
+                        let bytes = buffer.get(start..start + item_size).ok_or_else(|| {
+                            Error::OxifiveError(format!(
+                                "Chunk of {} bytes does not hold element {} of the chunk",
+                                buffer.len(),
+                                within
+                            ))
+                        })?;
+                        values.set(position, (decoder.element)(bytes, &mut decode_context)?);
+                    }
+                }
+                // Without a fill value, every selected element is in a chunk
+                Ok(unsafe { values.assume_init() })
             }
         }
-        log::info!("Array {:#?}", &array[[100, 100, 1]]);
-        Ok(array.into_dimensionality()?)
+    }
+
+    /// Decodes the value of elements that were never written, which is zero
+    /// unless the dataset defines a fill value.
+    fn decode_fill_value<T>(
+        &self,
+        datatype: &Datatype,
+        decode: &ElementDecoder<T>,
+        decode_context: &mut DecodeContext,
+    ) -> Result<T, Error> {
+        let size = datatype.size as usize;
+        match &self.data_object.fill_value {
+            Some(fill_value) if fill_value.len() == size => decode(fill_value, decode_context),
+            Some(fill_value) => Err(Error::OxifiveError(format!(
+                "Fill value of {} bytes does not match the datatype size {}",
+                fill_value.len(),
+                size
+            ))),
+            None => decode(&vec![0; size], decode_context),
+        }
     }
 }
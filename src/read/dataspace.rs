@@ -1,27 +1,68 @@
 use crate::error::Error;
+use crate::read::context::FormatContext;
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::Read;
 
-#[derive(Clone, Debug)]
-pub struct Dataspace {
-    pub shape: Vec<u64>,
+const MAX_DIMENSIONS_PRESENT: u8 = 0b0000_0001;
+const PERMUTATION_INDICES_PRESENT: u8 = 0b0000_0010;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dataspace {
+    /// A single element with no dimensions.
+    Scalar,
+    /// Dimensions with their maximum sizes, where `None` marks an unlimited
+    /// dimension. The maximum sizes are the current sizes unless stored.
+    Simple {
+        shape: Vec<u64>,
+        max_shape: Vec<Option<u64>>,
+    },
+    /// No elements at all, such as h5py's empty datasets and attributes.
+    Null,
+}
+
+impl Dataspace {
+    /// The dimensions of the dataspace, which are empty for scalar and null
+    /// dataspaces.
+    pub fn shape(&self) -> &[u64] {
+        match self {
+            Dataspace::Simple { shape, .. } => shape,
+            Dataspace::Scalar | Dataspace::Null => &[],
+        }
+    }
+
+    /// The maximum size of each dimension, with `None` for unlimited
+    /// dimensions that datasets can grow along.
+    pub fn max_shape(&self) -> &[Option<u64>] {
+        match self {
+            Dataspace::Simple { max_shape, .. } => max_shape,
+            Dataspace::Scalar | Dataspace::Null => &[],
+        }
+    }
 }
 
-pub fn parse_dataspace_message(input: &mut impl Read) -> Result<Dataspace, Error> {
+pub fn parse_dataspace_message(
+    input: &mut impl Read,
+    context: &FormatContext,
+) -> Result<Dataspace, Error> {
     let version = input.read_u8()?;
-    let dimensions = match version {
+    let (dimensions, flags, space_type) = match version {
         1 => {
             let dimensionality = input.read_u8()?;
-            let _flags = input.read_u8()?;
+            let flags = input.read_u8()?;
             let _reserved_0 = input.read_u8()?;
             let _reserved_1 = input.read_u32::<LittleEndian>()?;
-            dimensionality
+            // Version 1 has no space type, and scalars have no dimensions
+            (
+                dimensionality,
+                flags,
+                if dimensionality == 0 { 0 } else { 1 },
+            )
         }
         2 => {
             let dimensionality = input.read_u8()?;
-            let _flags = input.read_u8()?;
-            let _space_type = input.read_u8()?;
-            dimensionality
+            let flags = input.read_u8()?;
+            let space_type = input.read_u8()?;
+            (dimensionality, flags, space_type)
         }
         _ => {
             return Err(Error::OxifiveError(format!(
@@ -32,7 +73,34 @@ pub fn parse_dataspace_message(input: &mut impl Read) -> Result<Dataspace, Error
     };
     let mut shape = Vec::new();
     for _ in 0..dimensions {
-        shape.push(input.read_u64::<LittleEndian>()?);
+        shape.push(context.read_length(input)?);
+    }
+    let max_shape = if flags & MAX_DIMENSIONS_PRESENT != 0 {
+        // Unlimited dimensions have all bits set
+        let unlimited = u64::MAX >> (64 - 8 * u32::from(context.length_size));
+        (0..dimensions)
+            .map(|_| {
+                let size = context.read_length(input)?;
+                Ok(if size == unlimited { None } else { Some(size) })
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+    } else {
+        shape.iter().map(|&size| Some(size)).collect()
+    };
+    // Permutation indices were never implemented by the library, so they are
+    // only skipped
+    if version == 1 && flags & PERMUTATION_INDICES_PRESENT != 0 {
+        for _ in 0..dimensions {
+            context.read_length(input)?;
+        }
+    }
+    match space_type {
+        0 => Ok(Dataspace::Scalar),
+        1 => Ok(Dataspace::Simple { shape, max_shape }),
+        2 => Ok(Dataspace::Null),
+        _ => Err(Error::OxifiveError(format!(
+            "Unsupported dataspace type: {}",
+            space_type
+        ))),
     }
-    Ok(Dataspace { shape })
 }
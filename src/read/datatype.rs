@@ -1,4 +1,5 @@
 use crate::error::Error;
+use crate::read::dataset::DatatypeVerifiable;
 use byteorder::{LittleEndian, ReadBytesExt};
 use num_enum::TryFromPrimitive;
 use std::convert::TryFrom;
@@ -20,7 +21,144 @@ pub enum DatatypeEncoding {
     Array = 10,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ByteOrder {
+    LittleEndian,
+    BigEndian,
+    Vax,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum MantissaNormalization {
+    None = 0,
+    MostSignificantBitSet = 1,
+    MostSignificantBitImplied = 2,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum StringPadding {
+    NullTerminate = 0,
+    NullPad = 1,
+    SpacePad = 2,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum CharacterSet {
+    Ascii = 0,
+    Utf8 = 1,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum VariableLengthKind {
+    Sequence = 0,
+    String = 1,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum ReferenceKind {
+    Object = 0,
+    DatasetRegion = 1,
+}
+
+/// A member of a compound datatype. Array members of version 1 compound
+/// datatypes are given an array datatype.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompoundMember {
+    pub name: String,
+    pub byte_offset: u32,
+    pub datatype: Datatype,
+}
+
+impl CompoundMember {
+    /// Checks that this member can be read as the Rust type `T`.
+    pub fn verify<T: DatatypeVerifiable>(&self) -> Result<(), Error> {
+        T::verify(&self.datatype)
+    }
+
+    /// The bytes of this member within the bytes of a compound element.
+    pub fn bytes<'a>(&self, element: &'a [u8]) -> Result<&'a [u8], Error> {
+        let start = self.byte_offset as usize;
+        let end = start + self.datatype.size as usize;
+        element.get(start..end).ok_or_else(|| {
+            Error::OxifiveError(format!(
+                "Compound member {} at bytes {}..{} exceeds the element size {}",
+                self.name,
+                start,
+                end,
+                element.len()
+            ))
+        })
+    }
+}
+
+/// A member of an enumerated datatype, with its value in the byte order and
+/// size of the base type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumMember {
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DatatypeProperties {
+    FixedPoint {
+        signed: bool,
+        bit_offset: u16,
+        bit_precision: u16,
+    },
+    FloatingPoint {
+        sign_location: u8,
+        mantissa_normalization: MantissaNormalization,
+        bit_offset: u16,
+        bit_precision: u16,
+        exponent_location: u8,
+        exponent_size: u8,
+        mantissa_location: u8,
+        mantissa_size: u8,
+        exponent_bias: u32,
+    },
+    String {
+        padding: StringPadding,
+        character_set: CharacterSet,
+    },
+    VariableLength {
+        kind: VariableLengthKind,
+        padding: StringPadding,
+        character_set: CharacterSet,
+        base_type: Box<Datatype>,
+    },
+    Compound {
+        members: Vec<CompoundMember>,
+    },
+    Enumerated {
+        base_type: Box<Datatype>,
+        members: Vec<EnumMember>,
+    },
+    Array {
+        dimensions: Vec<u32>,
+        base_type: Box<Datatype>,
+    },
+    Reference {
+        kind: ReferenceKind,
+    },
+    Bitfield {
+        bit_offset: u16,
+        bit_precision: u16,
+    },
+    Time {
+        bit_precision: u16,
+    },
+    Opaque {
+        tag: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Datatype {
     pub class_and_version: u8,
     pub class_bit_field_0: u8,
@@ -28,17 +166,482 @@ pub struct Datatype {
     pub class_bit_field_2: u8,
     pub size: u32,
     pub encoding: DatatypeEncoding,
+    pub properties: DatatypeProperties,
+}
+
+impl Datatype {
+    /// An array datatype of the base type, as used for array members of
+    /// version 1 compound datatypes.
+    fn array(dimensions: Vec<u32>, base_type: Datatype) -> Datatype {
+        let count: u32 = dimensions.iter().product();
+        Datatype {
+            class_and_version: 0x20 | DatatypeEncoding::Array as u8,
+            class_bit_field_0: 0,
+            class_bit_field_1: 0,
+            class_bit_field_2: 0,
+            size: count * base_type.size,
+            encoding: DatatypeEncoding::Array,
+            properties: DatatypeProperties::Array {
+                dimensions,
+                base_type: Box::new(base_type),
+            },
+        }
+    }
+
+    /// The base type and dimensions of an array datatype, or this datatype and
+    /// no dimensions for other datatypes.
+    pub fn array_element(&self) -> (&Datatype, &[u32]) {
+        match &self.properties {
+            DatatypeProperties::Array {
+                dimensions,
+                base_type,
+            } => (base_type, dimensions),
+            _ => (self, &[]),
+        }
+    }
+
+    /// Byte order of fixed-point, floating-point, bitfield and time datatypes,
+    /// given by bit 0 of the class bit fields. Floating-point types also use
+    /// bit 6 to mark VAX byte order.
+    pub fn byte_order(&self) -> ByteOrder {
+        let vax = self.encoding == DatatypeEncoding::FloatingPoint
+            && self.class_bit_field_0 & 0b0100_0000 != 0;
+        match (vax, self.class_bit_field_0 & 0b0000_0001 != 0) {
+            (true, _) => ByteOrder::Vax,
+            (false, false) => ByteOrder::LittleEndian,
+            (false, true) => ByteOrder::BigEndian,
+        }
+    }
+
+    /// Reads the bytes of an element of at most 8 bytes into a u64 in native
+    /// byte order.
+    fn read_bits(&self, bytes: &[u8]) -> Result<u64, Error> {
+        if bytes.len() > 8 {
+            return Err(Error::OxifiveError(format!(
+                "Elements of {} bytes are too large to decode",
+                bytes.len()
+            )));
+        }
+        let mut value: u64 = 0;
+        match self.byte_order() {
+            ByteOrder::LittleEndian => {
+                for &byte in bytes.iter().rev() {
+                    value = (value << 8) | u64::from(byte);
+                }
+            }
+            ByteOrder::BigEndian => {
+                for &byte in bytes {
+                    value = (value << 8) | u64::from(byte);
+                }
+            }
+            ByteOrder::Vax => {
+                return Err(Error::OxifiveError(
+                    "VAX byte order is not supported".to_string(),
+                ))
+            }
+        }
+        Ok(value)
+    }
+
+    /// Decodes a fixed-point, bitfield or time element into the low bits of a
+    /// u64, honouring the bit offset and precision. Signed values are
+    /// sign-extended to 64 bits, so that casting to a narrower integer type
+    /// gives the right value.
+    pub fn decode_fixed_point(&self, bytes: &[u8]) -> Result<u64, Error> {
+        let (signed, bit_offset, bit_precision) = match self.properties {
+            DatatypeProperties::FixedPoint {
+                signed,
+                bit_offset,
+                bit_precision,
+            } => (signed, bit_offset, bit_precision),
+            DatatypeProperties::Bitfield {
+                bit_offset,
+                bit_precision,
+            } => (false, bit_offset, bit_precision),
+            DatatypeProperties::Time { bit_precision } => (false, 0, bit_precision),
+            _ => {
+                return Err(Error::OxifiveError(format!(
+                    "Expected a fixed-point datatype, found {:?}",
+                    self
+                )))
+            }
+        };
+        let mut value = self.read_bits(bytes)?;
+        value = value.checked_shr(u32::from(bit_offset)).unwrap_or(0);
+        if bit_precision == 0 {
+            return Ok(0);
+        }
+        if bit_precision < 64 {
+            value &= (1 << bit_precision) - 1;
+            if signed && (value >> (bit_precision - 1)) & 1 == 1 {
+                value |= !0 << bit_precision;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Finds a member of a compound datatype by name.
+    pub fn compound_member(&self, name: &str) -> Result<&CompoundMember, Error> {
+        match &self.properties {
+            DatatypeProperties::Compound { members } => members
+                .iter()
+                .find(|member| member.name == name)
+                .ok_or_else(|| Error::OxifiveError(format!("Compound member {} not found", name))),
+            _ => Err(Error::OxifiveError(format!(
+                "Expected a compound datatype, found {:?}",
+                self
+            ))),
+        }
+    }
+
+    /// The members of an enumerated datatype.
+    pub fn enum_members(&self) -> Result<&[EnumMember], Error> {
+        match &self.properties {
+            DatatypeProperties::Enumerated { members, .. } => Ok(members),
+            _ => Err(Error::OxifiveError(format!(
+                "Expected an enumerated datatype, found {:?}",
+                self
+            ))),
+        }
+    }
+
+    /// Finds the name of the enumerated datatype member with the given value.
+    pub fn enum_member_name(&self, bytes: &[u8]) -> Result<&str, Error> {
+        self.enum_members()?
+            .iter()
+            .find(|member| member.value == bytes)
+            .map(|member| &member.name[..])
+            .ok_or_else(|| {
+                Error::OxifiveError(format!("No enumerated member has the value {:?}", bytes))
+            })
+    }
+
+    /// Whether this is an IEEE 754 floating-point type with the given exponent
+    /// and mantissa sizes, which can be decoded directly by the Rust type.
+    pub fn is_ieee_float(&self, exponent_size: u8, mantissa_size: u8) -> bool {
+        let bits = 1 + exponent_size as u16 + mantissa_size as u16;
+        self.size as u16 * 8 == bits
+            && self.properties
+                == DatatypeProperties::FloatingPoint {
+                    sign_location: (bits - 1) as u8,
+                    mantissa_normalization: MantissaNormalization::MostSignificantBitImplied,
+                    bit_offset: 0,
+                    bit_precision: bits,
+                    exponent_location: mantissa_size,
+                    exponent_size,
+                    mantissa_location: 0,
+                    mantissa_size,
+                    exponent_bias: (1 << (exponent_size - 1)) - 1,
+                }
+    }
+
+    /// Decodes a floating-point element of any layout described by the
+    /// floating-point properties, such as half precision or 24-bit floats.
+    pub fn decode_floating_point(&self, bytes: &[u8]) -> Result<f64, Error> {
+        let (
+            sign_location,
+            mantissa_normalization,
+            exponent_location,
+            exponent_size,
+            mantissa_location,
+            mantissa_size,
+            exponent_bias,
+        ) = match self.properties {
+            DatatypeProperties::FloatingPoint {
+                sign_location,
+                mantissa_normalization,
+                exponent_location,
+                exponent_size,
+                mantissa_location,
+                mantissa_size,
+                exponent_bias,
+                ..
+            } => (
+                sign_location,
+                mantissa_normalization,
+                exponent_location,
+                exponent_size,
+                mantissa_location,
+                mantissa_size,
+                exponent_bias,
+            ),
+            _ => {
+                return Err(Error::OxifiveError(format!(
+                    "Expected a floating-point datatype, found {:?}",
+                    self
+                )))
+            }
+        };
+        let bits = self.read_bits(bytes)?;
+        let field = |location: u8, size: u8| -> u64 {
+            let value = bits.checked_shr(u32::from(location)).unwrap_or(0);
+            if size >= 64 {
+                value
+            } else {
+                value & ((1 << size) - 1)
+            }
+        };
+        let negative = field(sign_location, 1) == 1;
+        let exponent = field(exponent_location, exponent_size);
+        let mantissa = field(mantissa_location, mantissa_size) as f64;
+        let max_exponent = (1u64 << exponent_size.min(63)) - 1;
+        let fraction = mantissa / 2f64.powi(i32::from(mantissa_size));
+        let value = match mantissa_normalization {
+            MantissaNormalization::MostSignificantBitImplied if exponent == max_exponent => {
+                if mantissa == 0.0 {
+                    f64::INFINITY
+                } else {
+                    f64::NAN
+                }
+            }
+            MantissaNormalization::MostSignificantBitImplied if exponent == 0 => {
+                fraction * 2f64.powi(1 - exponent_bias as i32)
+            }
+            MantissaNormalization::MostSignificantBitImplied => {
+                (1.0 + fraction) * 2f64.powi(exponent as i32 - exponent_bias as i32)
+            }
+            MantissaNormalization::MostSignificantBitSet => {
+                2.0 * fraction * 2f64.powi(exponent as i32 - exponent_bias as i32)
+            }
+            MantissaNormalization::None => {
+                fraction * 2f64.powi(exponent as i32 - exponent_bias as i32)
+            }
+        };
+        Ok(if negative { -value } else { value })
+    }
+}
+
+/// Decodes the bytes of a fixed-length or variable-length string, removing the
+/// padding and checking that the bytes are valid in the character set.
+pub fn decode_string(
+    bytes: &[u8],
+    padding: StringPadding,
+    character_set: CharacterSet,
+) -> Result<String, Error> {
+    let bytes = match padding {
+        // A null-terminated string that fills the whole element has no
+        // terminator, and null-padded strings end at the first null byte.
+        StringPadding::NullTerminate | StringPadding::NullPad => {
+            let end = bytes.iter().position(|&byte| byte == 0);
+            &bytes[..end.unwrap_or(bytes.len())]
+        }
+        StringPadding::SpacePad => {
+            let end = bytes.iter().rposition(|&byte| byte != b' ');
+            &bytes[..end.map_or(0, |end| end + 1)]
+        }
+    };
+    if character_set == CharacterSet::Ascii && !bytes.is_ascii() {
+        return Err(Error::OxifiveError(format!(
+            "Invalid ASCII string: {:?}",
+            String::from_utf8_lossy(bytes)
+        )));
+    }
+    String::from_utf8(bytes.to_vec())
+        .map_err(|error| Error::OxifiveError(format!("Invalid UTF-8 string: {}", error)))
+}
+
+/// Reads a null-terminated compound or enumerated member name. Versions 1 and 2
+/// pad the name with nulls to a multiple of eight bytes.
+fn read_member_name(input: &mut impl Read, padded: bool) -> Result<String, Error> {
+    let mut name = vec![];
+    loop {
+        let byte = input.read_u8()?;
+        if byte == 0 {
+            break;
+        }
+        name.push(byte);
+    }
+    if padded {
+        let padding = 7 - name.len() % 8;
+        let mut buffer = vec![0; padding];
+        input.read_exact(&mut buffer)?;
+    }
+    Ok(String::from_utf8(name)?)
+}
+
+fn parse_compound_members(
+    input: &mut impl Read,
+    version: u8,
+    member_count: u16,
+    size: u32,
+) -> Result<Vec<CompoundMember>, Error> {
+    // Version 3 stores member offsets in as few bytes as the size allows
+    let offset_size = match size {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x10000..=0xFF_FFFF => 3,
+        _ => 4,
+    };
+    let mut members = Vec::with_capacity(member_count as usize);
+    for _ in 0..member_count {
+        let member = match version {
+            1 => {
+                let name = read_member_name(input, true)?;
+                let byte_offset = input.read_u32::<LittleEndian>()?;
+                let dimensionality = input.read_u8()?;
+                let mut reserved = [0; 3];
+                input.read_exact(&mut reserved)?;
+                let _permutation = input.read_u32::<LittleEndian>()?;
+                let _reserved = input.read_u32::<LittleEndian>()?;
+                let mut dimensions = vec![];
+                for index in 0..4 {
+                    let dimension = input.read_u32::<LittleEndian>()?;
+                    if index < dimensionality {
+                        dimensions.push(dimension);
+                    }
+                }
+                let datatype = parse_datatype_message(input)?;
+                CompoundMember {
+                    name,
+                    byte_offset,
+                    datatype: if dimensions.is_empty() {
+                        datatype
+                    } else {
+                        Datatype::array(dimensions, datatype)
+                    },
+                }
+            }
+            2 => CompoundMember {
+                name: read_member_name(input, true)?,
+                byte_offset: input.read_u32::<LittleEndian>()?,
+                datatype: parse_datatype_message(input)?,
+            },
+            3 => CompoundMember {
+                name: read_member_name(input, false)?,
+                byte_offset: input.read_uint::<LittleEndian>(offset_size)? as u32,
+                datatype: parse_datatype_message(input)?,
+            },
+            _ => {
+                return Err(Error::OxifiveError(format!(
+                    "Unsupported compound datatype version {}",
+                    version
+                )))
+            }
+        };
+        members.push(member);
+    }
+    Ok(members)
 }
 
 pub fn parse_datatype_message(input: &mut impl Read) -> Result<Datatype, Error> {
     let class_and_version = input.read_u8()?;
+    let encoding = DatatypeEncoding::try_from(class_and_version & 0x0F)?;
+    let class_bit_field_0 = input.read_u8()?;
+    let class_bit_field_1 = input.read_u8()?;
+    let class_bit_field_2 = input.read_u8()?;
+    let size = input.read_u32::<LittleEndian>()?;
+    // Elements are split by the size of their datatype
+    if size == 0 {
+        return Err(Error::OxifiveError(format!(
+            "Datatype of class {:?} has a size of zero",
+            encoding
+        )));
+    }
+    let properties = match encoding {
+        DatatypeEncoding::FixedPoint => DatatypeProperties::FixedPoint {
+            signed: class_bit_field_0 & 0b0000_1000 != 0,
+            bit_offset: input.read_u16::<LittleEndian>()?,
+            bit_precision: input.read_u16::<LittleEndian>()?,
+        },
+        DatatypeEncoding::FloatingPoint => DatatypeProperties::FloatingPoint {
+            sign_location: class_bit_field_1,
+            mantissa_normalization: MantissaNormalization::try_from(
+                (class_bit_field_0 >> 4) & 0b11,
+            )?,
+            bit_offset: input.read_u16::<LittleEndian>()?,
+            bit_precision: input.read_u16::<LittleEndian>()?,
+            exponent_location: input.read_u8()?,
+            exponent_size: input.read_u8()?,
+            mantissa_location: input.read_u8()?,
+            mantissa_size: input.read_u8()?,
+            exponent_bias: input.read_u32::<LittleEndian>()?,
+        },
+        DatatypeEncoding::String => DatatypeProperties::String {
+            padding: StringPadding::try_from(class_bit_field_0 & 0x0F)?,
+            character_set: CharacterSet::try_from(class_bit_field_0 >> 4)?,
+        },
+        DatatypeEncoding::VariableLength => DatatypeProperties::VariableLength {
+            kind: VariableLengthKind::try_from(class_bit_field_0 & 0x0F)?,
+            padding: StringPadding::try_from(class_bit_field_0 >> 4)?,
+            character_set: CharacterSet::try_from(class_bit_field_1 & 0x0F)?,
+            base_type: Box::new(parse_datatype_message(input)?),
+        },
+        DatatypeEncoding::Compound => DatatypeProperties::Compound {
+            members: parse_compound_members(
+                input,
+                class_and_version >> 4,
+                u16::from(class_bit_field_0) | u16::from(class_bit_field_1) << 8,
+                size,
+            )?,
+        },
+        DatatypeEncoding::Enumerated => {
+            let base_type = parse_datatype_message(input)?;
+            let member_count = u16::from(class_bit_field_0) | u16::from(class_bit_field_1) << 8;
+            let names = (0..member_count)
+                .map(|_| read_member_name(input, class_and_version >> 4 < 3))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let mut members = Vec::with_capacity(names.len());
+            for name in names {
+                let mut value = vec![0; base_type.size as usize];
+                input.read_exact(&mut value)?;
+                members.push(EnumMember { name, value });
+            }
+            DatatypeProperties::Enumerated {
+                base_type: Box::new(base_type),
+                members,
+            }
+        }
+        DatatypeEncoding::Array => {
+            let version = class_and_version >> 4;
+            let dimensionality = input.read_u8()?;
+            if version < 3 {
+                let mut reserved = [0; 3];
+                input.read_exact(&mut reserved)?;
+            }
+            let dimensions = (0..dimensionality)
+                .map(|_| input.read_u32::<LittleEndian>())
+                .collect::<Result<Vec<_>, _>>()?;
+            // Version 2 stores a permutation index for every dimension, which
+            // the library has never used
+            if version < 3 {
+                for _ in 0..dimensionality {
+                    input.read_u32::<LittleEndian>()?;
+                }
+            }
+            DatatypeProperties::Array {
+                dimensions,
+                base_type: Box::new(parse_datatype_message(input)?),
+            }
+        }
+        DatatypeEncoding::Reference => DatatypeProperties::Reference {
+            kind: ReferenceKind::try_from(class_bit_field_0 & 0x0F)?,
+        },
+        DatatypeEncoding::Bitfield => DatatypeProperties::Bitfield {
+            bit_offset: input.read_u16::<LittleEndian>()?,
+            bit_precision: input.read_u16::<LittleEndian>()?,
+        },
+        DatatypeEncoding::Time => DatatypeProperties::Time {
+            bit_precision: input.read_u16::<LittleEndian>()?,
+        },
+        DatatypeEncoding::Opaque => {
+            // The tag is padded with nulls to the length in the bit field
+            let mut tag = vec![0; class_bit_field_0 as usize];
+            input.read_exact(&mut tag)?;
+            let end = tag.iter().position(|&byte| byte == 0).unwrap_or(tag.len());
+            tag.truncate(end);
+            DatatypeProperties::Opaque {
+                tag: String::from_utf8(tag)?,
+            }
+        }
+    };
     let datatype = Datatype {
         class_and_version,
-        class_bit_field_0: input.read_u8()?,
-        class_bit_field_1: input.read_u8()?,
-        class_bit_field_2: input.read_u8()?,
-        size: input.read_u32::<LittleEndian>()?,
-        encoding: DatatypeEncoding::try_from(class_and_version & 0x0F)?,
+        class_bit_field_0,
+        class_bit_field_1,
+        class_bit_field_2,
+        size,
+        encoding,
+        properties,
     };
 
     Ok(datatype)
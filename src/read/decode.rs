@@ -0,0 +1,43 @@
+use crate::read::{
+    context::FormatContext, conversion::ConversionPolicy, global_heap::GlobalHeap, io::ReadSeek,
+};
+
+/// State shared by the element decoders of one read: the global heap for
+/// variable-length data, the format context for addresses stored in elements
+/// and the policy for numeric conversions.
+pub struct DecodeContext<'a> {
+    heap: GlobalHeap<'a>,
+    format_context: FormatContext,
+    conversion: ConversionPolicy,
+}
+
+impl<'a> DecodeContext<'a> {
+    pub fn new(
+        input: &'a mut dyn ReadSeek,
+        format_context: FormatContext,
+        conversion: ConversionPolicy,
+    ) -> Self {
+        DecodeContext {
+            heap: GlobalHeap::new(input, format_context),
+            format_context,
+            conversion,
+        }
+    }
+
+    /// The global heap that variable-length data and region references are
+    /// read from.
+    pub fn heap(&mut self) -> &mut GlobalHeap<'a> {
+        &mut self.heap
+    }
+
+    /// The format context used to read addresses, such as those stored in
+    /// references.
+    pub fn format_context(&self) -> FormatContext {
+        self.format_context
+    }
+
+    /// How numeric values that do not fit the Rust type are handled.
+    pub fn conversion(&self) -> ConversionPolicy {
+        self.conversion
+    }
+}
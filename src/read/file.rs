@@ -1,20 +1,26 @@
+use ndarray::Array1;
+use std::fmt::Debug;
+use std::io::SeekFrom;
 use std::sync::{Arc, Mutex};
 
-use crate::Object;
 use crate::error::Error;
 use crate::read::{
+    context::FormatContext,
     data_object::{self, DataObject},
-    dataset::Dataset,
+    dataset::{Dataset, DatatypeVerifiable},
+    datatype::Datatype,
     group::Group,
     io::ReadSeek,
-    superblock::{self, SuperBlockVersion0},
-    symbol_table::SymbolTableEntry,
+    reference::{ObjectReference, RegionReference},
+    superblock::{self, SuperBlock},
 };
+use crate::Object;
 
 pub struct FileReader<R> {
-    pub superblock: SuperBlockVersion0,
-    pub root_entry: SymbolTableEntry,
+    pub superblock: SuperBlock,
+    pub superblock_extension: Option<DataObject>,
     pub data_object: DataObject,
+    pub context: FormatContext,
     input: Arc<Mutex<R>>,
 }
 
@@ -22,20 +28,45 @@ impl<R: ReadSeek> FileReader<R> {
     pub fn new(input: R) -> Result<Self, Error> {
         let input = Arc::new(Mutex::new(input));
         let reader = &mut *input.lock().unwrap();
+        let superblock_address = superblock::find_superblock(reader)?;
+        reader.seek(SeekFrom::Start(superblock_address))?;
         let superblock = superblock::parse_superblock(reader)?;
         log::info!("{:#?}", superblock);
-        let root_entry = SymbolTableEntry::read(reader)?;
-        log::info!("{:#?}", root_entry);
-        let offset_to_data_objects = root_entry.object_header_address;
-        let data_object = data_object::parse_data_object(reader, offset_to_data_objects)?;
+        let context = FormatContext::from_superblock(&superblock, superblock_address, None)?;
+        let superblock_extension = match superblock.superblock_extension_address() {
+            Some(address) => Some(data_object::parse_data_object(reader, &context, address)?),
+            None => None,
+        };
+        let context = FormatContext::from_superblock(
+            &superblock,
+            superblock_address,
+            superblock_extension.as_ref(),
+        )?;
+        let data_object =
+            data_object::parse_data_object(reader, &context, superblock.root_group_address())?;
         Ok(Self {
             superblock,
-            root_entry,
+            superblock_extension,
             data_object,
+            context,
             input: input.clone(),
         })
     }
 
+    /// Size of the userblock, the user-defined data that precedes the
+    /// superblock.
+    pub fn userblock_size(&self) -> u64 {
+        self.context.base_address
+    }
+
+    pub fn userblock(&self) -> Result<Vec<u8>, Error> {
+        let mut input = self.input.lock().unwrap();
+        input.seek(SeekFrom::Start(0))?;
+        let mut userblock = vec![0; self.userblock_size() as usize];
+        input.read_exact(&mut userblock)?;
+        Ok(userblock)
+    }
+
     pub fn keys(&self) -> Vec<String> {
         self.as_group().keys()
     }
@@ -43,6 +74,7 @@ impl<R: ReadSeek> FileReader<R> {
     pub fn as_mut_group(&mut self) -> Group<R> {
         Group {
             data_object: self.data_object.clone(),
+            context: self.context,
             input: self.input.clone(),
         }
     }
@@ -50,6 +82,7 @@ impl<R: ReadSeek> FileReader<R> {
     pub fn as_group(&self) -> Group<R> {
         Group {
             data_object: self.data_object.clone(),
+            context: self.context,
             input: self.input.clone(),
         }
     }
@@ -65,5 +98,32 @@ impl<R: ReadSeek> FileReader<R> {
     pub fn dataset(&self, index: &str) -> Result<Dataset<R>, Error> {
         self.as_group().dataset(index)
     }
-}
 
+    pub fn datatype(&self, index: &str) -> Result<Datatype, Error> {
+        self.as_group().datatype(index)
+    }
+
+    /// Finds the group or dataset that an object reference points to.
+    pub fn dereference(&self, reference: &ObjectReference) -> Result<Object<R>, Error> {
+        if reference.is_null() {
+            return Err(Error::OxifiveError(
+                "Cannot dereference a null object reference".to_string(),
+            ));
+        }
+        Object::parse(&self.input, &self.context, reference.address)
+    }
+
+    /// Reads the elements of the dataset region that a region reference
+    /// points to.
+    pub fn read_region<T>(&self, reference: &RegionReference) -> Result<Array1<T>, Error>
+    where
+        T: Clone + Debug + DatatypeVerifiable,
+    {
+        match self.dereference(&reference.dataset)? {
+            Object::Dataset(dataset) => dataset.read_selection(&reference.selection),
+            _ => Err(Error::OxifiveError(
+                "Region reference does not point to a dataset".to_string(),
+            )),
+        }
+    }
+}
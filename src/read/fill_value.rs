@@ -0,0 +1,53 @@
+use crate::error::Error;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::Read;
+
+const FILL_VALUE_DEFINED: u8 = 0b0010_0000;
+
+fn read_fill_value(input: &mut impl Read) -> Result<Option<Vec<u8>>, Error> {
+    let size = input.read_u32::<LittleEndian>()?;
+    // A defined fill value without any bytes is the default of zeros
+    if size == 0 {
+        return Ok(None);
+    }
+    let mut value = vec![0; size as usize];
+    input.read_exact(&mut value)?;
+    Ok(Some(value))
+}
+
+/// Parses a fill value message, returning the bytes of the fill value or `None`
+/// when elements that were never written hold zeros.
+pub fn parse_fill_value_message(input: &mut impl Read) -> Result<Option<Vec<u8>>, Error> {
+    let version = input.read_u8()?;
+    match version {
+        1 | 2 => {
+            let _space_allocation_time = input.read_u8()?;
+            let _fill_value_write_time = input.read_u8()?;
+            let defined = input.read_u8()?;
+            // Version 1 always stores the size, even without a fill value
+            if version == 1 || defined != 0 {
+                read_fill_value(input)
+            } else {
+                Ok(None)
+            }
+        }
+        3 => {
+            let flags = input.read_u8()?;
+            if flags & FILL_VALUE_DEFINED != 0 {
+                read_fill_value(input)
+            } else {
+                Ok(None)
+            }
+        }
+        _ => Err(Error::OxifiveError(format!(
+            "Unsupported fill value version: {}",
+            version
+        ))),
+    }
+}
+
+/// Parses the old fill value message, which only holds the fill value and is
+/// superseded by a fill value message in the same object header.
+pub fn parse_old_fill_value_message(input: &mut impl Read) -> Result<Option<Vec<u8>>, Error> {
+    read_fill_value(input)
+}
@@ -44,7 +44,7 @@ pub fn parse_filter_pipeline_message(
         let _flags = input.read_u16::<LittleEndian>()?;
         let client_data_value_count = input.read_u16::<LittleEndian>()?;
 
-        let mut name_bytes_padded = vec![0; name_length_padded as usize];
+        let mut name_bytes_padded = vec![0; name_length_padded];
         input.read_exact(&mut name_bytes_padded)?;
         assert!(name_bytes_padded[name_length - 1] == 0);
         let name_bytes = name_bytes_padded[0..name_length - 1].to_vec();
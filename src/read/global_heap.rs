@@ -0,0 +1,147 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::{collections::HashMap, convert::TryFrom, io::Cursor};
+
+use crate::{
+    error::Error,
+    read::{context::FormatContext, io::ReadSeek},
+};
+
+/// Objects of a global heap collection, keyed by their heap object index.
+#[derive(Clone, Debug)]
+pub struct GlobalHeapCollection {
+    pub version: u8,
+    pub collection_size: u64,
+    pub objects: HashMap<u16, Vec<u8>>,
+}
+
+impl GlobalHeapCollection {
+    pub fn read(
+        input: &mut (impl ReadSeek + ?Sized),
+        context: &FormatContext,
+        address: u64,
+    ) -> Result<GlobalHeapCollection, Error> {
+        context.seek(input, address)?;
+        let mut signature = [0; 4];
+        input.read_exact(&mut signature)?;
+        if &signature != b"GCOL" {
+            return Err(Error::OxifiveError(format!(
+                "Wrong global heap collection signature: {:?}",
+                signature
+            )));
+        }
+        let version = input.read_u8()?;
+        if version != 1 {
+            return Err(Error::OxifiveError(format!(
+                "Unsupported global heap collection version {}",
+                version
+            )));
+        }
+        let mut reserved = [0; 3];
+        input.read_exact(&mut reserved)?;
+        let collection_size = context.read_length(input)?;
+
+        // The collection size includes the header that was just read
+        let header_size = 8 + context.length_size as u64;
+        if collection_size < header_size {
+            return Err(Error::OxifiveError(format!(
+                "Invalid global heap collection size {}",
+                collection_size
+            )));
+        }
+        let mut buffer = vec![0; (collection_size - header_size) as usize];
+        input.read_exact(&mut buffer)?;
+
+        let object_header_size = 8 + context.length_size as usize;
+        let mut objects = HashMap::new();
+        let mut cursor = Cursor::new(&buffer[..]);
+        while (cursor.position() as usize) + object_header_size <= buffer.len() {
+            let index = cursor.read_u16::<LittleEndian>()?;
+            // Object 0 is the free space at the end of the collection
+            if index == 0 {
+                break;
+            }
+            let _reference_count = cursor.read_u16::<LittleEndian>()?;
+            let _reserved = cursor.read_u32::<LittleEndian>()?;
+            let size = context.read_length(&mut cursor)? as usize;
+            let start = cursor.position() as usize;
+            if start + size > buffer.len() {
+                return Err(Error::OxifiveError(format!(
+                    "Global heap object {} of size {} exceeds its collection",
+                    index, size
+                )));
+            }
+            objects.insert(index, buffer[start..start + size].to_vec());
+            // Object data is padded to a multiple of eight bytes
+            cursor.set_position((start + size.div_ceil(8) * 8) as u64);
+        }
+
+        Ok(GlobalHeapCollection {
+            version,
+            collection_size,
+            objects,
+        })
+    }
+}
+
+/// Reads objects from the global heap, caching every collection that has been
+/// read so that elements sharing a collection only read it once.
+pub struct GlobalHeap<'a> {
+    input: &'a mut dyn ReadSeek,
+    context: FormatContext,
+    collections: HashMap<u64, GlobalHeapCollection>,
+}
+
+impl<'a> GlobalHeap<'a> {
+    pub fn new(input: &'a mut dyn ReadSeek, context: FormatContext) -> Self {
+        GlobalHeap {
+            input,
+            context,
+            collections: HashMap::new(),
+        }
+    }
+
+    /// The input that the heap reads from, for reading other data in between
+    /// heap objects without giving up the cache.
+    pub fn input(&mut self) -> &mut dyn ReadSeek {
+        self.input
+    }
+
+    pub fn object(&mut self, collection_address: u64, index: u32) -> Result<&[u8], Error> {
+        // Objects of a collection are numbered with 16 bits
+        let index = u16::try_from(index).map_err(|_| {
+            Error::OxifiveError(format!(
+                "Global heap object index {} is out of range",
+                index
+            ))
+        })?;
+        if !self.collections.contains_key(&collection_address) {
+            let collection =
+                GlobalHeapCollection::read(self.input, &self.context, collection_address)?;
+            self.collections.insert(collection_address, collection);
+        }
+        self.collections[&collection_address]
+            .objects
+            .get(&index)
+            .map(|object| &object[..])
+            .ok_or_else(|| {
+                Error::OxifiveError(format!(
+                    "Global heap object {} not found in collection at {}",
+                    index, collection_address
+                ))
+            })
+    }
+
+    /// Reads the data of a variable-length element, which consists of its
+    /// length followed by the global heap ID of its data.
+    pub fn variable_length_data(&mut self, bytes: &[u8]) -> Result<(u32, &[u8]), Error> {
+        let mut cursor = Cursor::new(bytes);
+        let length = cursor.read_u32::<LittleEndian>()?;
+        let collection_address = self.context.read_offset(&mut cursor)?;
+        let index = cursor.read_u32::<LittleEndian>()?;
+        // Empty elements are stored with a zero length and no heap object
+        if length == 0 {
+            return Ok((0, &[]));
+        }
+        Ok((length, self.object(collection_address, index)?))
+    }
+}
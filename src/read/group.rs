@@ -1,13 +1,8 @@
-use std::io::Read;
-use std::ops::Index;
 use std::sync::{Arc, Mutex};
 
 use crate::error::Error;
 use crate::read::{
-    data_object::{parse_data_object, DataObject},
-    dataset::Dataset,
-    file::FileReader,
-    link::Link,
+    context::FormatContext, data_object::DataObject, dataset::Dataset, datatype::Datatype,
     link::LinkTarget,
 };
 use crate::{Object, ReadSeek};
@@ -15,16 +10,13 @@ use crate::{Object, ReadSeek};
 #[derive(Clone, Debug)]
 pub struct Group<R> {
     pub data_object: DataObject,
+    pub context: FormatContext,
     pub input: Arc<Mutex<R>>,
 }
 
 impl<R: ReadSeek> Group<R> {
     pub fn keys(&self) -> Vec<String> {
-        self.data_object
-            .links
-            .keys()
-            .map(|key| key.clone())
-            .collect()
+        self.data_object.links.keys().cloned().collect()
     }
 
     pub fn object(&self, name: &str) -> Result<Object<R>, Error> {
@@ -38,18 +30,7 @@ impl<R: ReadSeek> Group<R> {
                 )))
             }
         };
-        let data_object = parse_data_object(&mut *self.input.lock().unwrap(), data_address)?;
-        if data_object.is_group() {
-            Ok(Object::Group(Group {
-                data_object,
-                input: self.input.clone(),
-            }))
-        } else {
-            Ok(Object::Dataset(Dataset {
-                data_object,
-                input: self.input.clone(),
-            }))
-        }
+        Object::parse(&self.input, &self.context, data_address)
     }
 
     pub fn group(&self, name: &str) -> Result<Group<R>, Error> {
@@ -65,4 +46,12 @@ impl<R: ReadSeek> Group<R> {
             _ => Err(Error::OxifiveError(format!("{name} is not a dataset"))),
         }
     }
+
+    /// Finds a committed datatype in this group.
+    pub fn datatype(&self, name: &str) -> Result<Datatype, Error> {
+        match self.object(name)? {
+            Object::Datatype(datatype) => Ok(datatype),
+            _ => Err(Error::OxifiveError(format!("{name} is not a datatype"))),
+        }
+    }
 }
@@ -1,27 +1,23 @@
 use crate::error::Error;
+use crate::read::context::FormatContext;
 use crate::read::io::ReadSeek;
 use crate::read::node::BTreeNodeType;
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::io::SeekFrom;
 
 #[derive(Clone, Debug)]
 pub struct GroupBTreeNode {
-    pub node_type: BTreeNodeType,
     pub node_level: u8,
-    pub entries_used: u16,
-    pub left_sibling: u64,
-    pub right_sibling: u64,
-    pub keys: Vec<u64>,
     pub addresses: Vec<u64>,
 }
 
 pub fn parse_group_btree(
     input: &mut impl ReadSeek,
+    context: &FormatContext,
     group: u64,
 ) -> Result<Vec<GroupBTreeNode>, Error> {
-    let root_node = parse_group_btree_node(input, group)?;
+    let root_node = parse_group_btree_node(input, context, group)?;
 
     let mut nodes = HashMap::<u8, Vec<GroupBTreeNode>>::new();
     let mut node_level = root_node.node_level;
@@ -30,7 +26,7 @@ pub fn parse_group_btree(
         let mut next_nodes = vec![];
         for parent_node in &nodes[&node_level] {
             for &group in &parent_node.addresses {
-                next_nodes.push(parse_group_btree_node(input, group)?);
+                next_nodes.push(parse_group_btree_node(input, context, group)?);
             }
         }
         let next_node_level = next_nodes[0].node_level;
@@ -43,9 +39,10 @@ pub fn parse_group_btree(
 
 pub fn parse_group_btree_node(
     input: &mut impl ReadSeek,
+    context: &FormatContext,
     offset: u64,
 ) -> Result<GroupBTreeNode, Error> {
-    input.seek(SeekFrom::Start(offset))?;
+    context.seek(input, offset)?;
     let signature = {
         let mut bytes = [0; 4];
         input.read_exact(&mut bytes)?;
@@ -65,26 +62,21 @@ pub fn parse_group_btree_node(
     }
     let node_level = input.read_u8()?;
     let entries_used = input.read_u16::<LittleEndian>()?;
-    let left_sibling = input.read_u64::<LittleEndian>()?;
-    let right_sibling = input.read_u64::<LittleEndian>()?;
+    // Every level is read from the child pointers of its parents, so the
+    // sibling pointers are skipped
+    let _left_sibling = context.read_offset(input)?;
+    let _right_sibling = context.read_offset(input)?;
 
-    let mut keys = vec![];
     let mut addresses = vec![];
 
     for _ in 0..entries_used {
-        let key = input.read_u64::<LittleEndian>()?;
-        let group = input.read_u64::<LittleEndian>()?;
-        keys.push(key);
-        addresses.push(group);
+        // The keys are heap offsets of names, which are only needed for lookups
+        let _key = context.read_length(input)?;
+        addresses.push(context.read_offset(input)?);
     }
 
     Ok(GroupBTreeNode {
-        node_type,
         node_level,
-        entries_used,
-        left_sibling,
-        right_sibling,
-        keys,
         addresses,
     })
 }
@@ -1,17 +1,14 @@
 use crate::error::Error;
+use crate::read::context::FormatContext;
 use crate::read::group_btree::parse_group_btree;
 use crate::read::io::ReadSeek;
 use crate::read::local_heap::LocalHeap;
-use crate::read::object::Object;
 use crate::read::symbol_table::{SymbolTableEntry, SymbolTableNode};
-use crate::{Dataset, FileReader, Group};
 use bitflags::bitflags;
 use byteorder::{LittleEndian, ReadBytesExt};
 use num_enum::TryFromPrimitive;
 use std::convert::TryFrom;
-use std::io::{Cursor, Read, SeekFrom};
-
-use super::data_object::parse_data_object;
+use std::io::Cursor;
 
 bitflags! {
     pub struct LinkFlags : u8 {
@@ -66,26 +63,28 @@ struct SymbolTableMessage {
 
 pub fn parse_symbol_table_message(
     input: &mut impl ReadSeek,
+    context: &FormatContext,
     message_cursor: &mut impl ReadSeek,
 ) -> Result<Vec<Link>, Error> {
     let symbol_table_message = SymbolTableMessage {
-        btree_address: message_cursor.read_u64::<LittleEndian>()?,
-        heap_address: message_cursor.read_u64::<LittleEndian>()?,
+        btree_address: context.read_offset(message_cursor)?,
+        heap_address: context.read_offset(message_cursor)?,
     };
     let mut links = vec![];
     // TODO verify value < usize
-    let btree_nodes = parse_group_btree(input, symbol_table_message.btree_address)?;
-    let heap = LocalHeap::read(input, symbol_table_message.heap_address)?;
+    let btree_nodes = parse_group_btree(input, context, symbol_table_message.btree_address)?;
+    let heap = LocalHeap::read(input, context, symbol_table_message.heap_address)?;
 
-    for address in btree_nodes.iter().map(|n| n.addresses.clone()).flatten() {
-        input.seek(SeekFrom::Start(address))?;
+    for address in btree_nodes.iter().flat_map(|n| n.addresses.clone()) {
+        context.seek(input, address)?;
         let symbol_table_node = SymbolTableNode::read(input)?;
         let mut symbol_table = vec![];
         for _ in 0..symbol_table_node.symbols {
-            symbol_table.push(SymbolTableEntry::read(input)?);
+            symbol_table.push(SymbolTableEntry::read(input, context)?);
         }
         for symbol_table_entry in symbol_table {
-            let link_name = heap.object_name(input, symbol_table_entry.link_name_offset)?;
+            let link_name =
+                heap.object_name(input, context, symbol_table_entry.link_name_offset)?;
             match symbol_table_entry.cache_type {
                 0 | 1 => {
                     links.push(Link {
@@ -100,7 +99,7 @@ pub fn parse_symbol_table_message(
                 2 => {
                     let mut scratch_cursor = Cursor::new(symbol_table_entry.scratch);
                     let offset = scratch_cursor.read_u32::<LittleEndian>()?;
-                    let link_target = heap.object_name(input, offset.into())?;
+                    let link_target = heap.object_name(input, context, offset.into())?;
                     links.push(Link {
                         version: 0,
                         flags: LinkFlags::empty(),
@@ -118,7 +117,10 @@ pub fn parse_symbol_table_message(
     Ok(links)
 }
 
-pub fn parse_link_message(input: &mut impl ReadSeek) -> Result<Link, Error> {
+pub fn parse_link_message(
+    input: &mut impl ReadSeek,
+    context: &FormatContext,
+) -> Result<Link, Error> {
     let version = input.read_u8()?;
     let flags = LinkFlags::new(input.read_u8()?);
     let link_type = if flags.contains(LinkFlags::LINK_TYPE_FIELD_PRESENT) {
@@ -174,7 +176,7 @@ pub fn parse_link_message(input: &mut impl ReadSeek) -> Result<Link, Error> {
                 }
             }
             LinkType::Hard => LinkTarget::Hard {
-                address: input.read_u64::<LittleEndian>()?,
+                address: context.read_offset(input)?,
             },
             _ => {
                 return Err(Error::OxifiveError(format!(
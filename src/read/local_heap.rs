@@ -1,49 +1,65 @@
-use std::io::SeekFrom;
+use byteorder::ReadBytesExt;
 
-use byteorder::{LittleEndian, ReadBytesExt};
-
-use crate::{error::Error, read::io::ReadSeek};
+use crate::{
+    error::Error,
+    read::{context::FormatContext, io::ReadSeek},
+};
 
 pub struct LocalHeap {
-    pub signature: [u8; 4],
-    pub version: u8,
-    pub reserved: [u8; 3],
     pub data_segment_size: u64,
-    pub offset_to_free_list: u64,
     pub address_of_data_segment: u64,
 }
 
 impl LocalHeap {
-    pub fn read(input: &mut impl ReadSeek, address: u64) -> Result<LocalHeap, Error> {
-        input.seek(SeekFrom::Start(address))?;
-        let local_heap = LocalHeap {
-            signature: {
-                let mut result = [0; 4];
-                input.read_exact(&mut result)?;
-                result
-            },
-            version: input.read_u8()?,
-            reserved: {
-                let mut result = [0; 3];
-                input.read_exact(&mut result)?;
-                result
-            },
-            data_segment_size: input.read_u64::<LittleEndian>()?,
-            offset_to_free_list: input.read_u64::<LittleEndian>()?,
-            address_of_data_segment: input.read_u64::<LittleEndian>()?,
-        };
-        assert!(local_heap.signature == "HEAP".as_bytes());
-        assert!(local_heap.version == 0);
-        Ok(local_heap)
+    pub fn read(
+        input: &mut impl ReadSeek,
+        context: &FormatContext,
+        address: u64,
+    ) -> Result<LocalHeap, Error> {
+        context.seek(input, address)?;
+        let mut signature = [0; 4];
+        input.read_exact(&mut signature)?;
+        if &signature != b"HEAP" {
+            return Err(Error::OxifiveError(format!(
+                "Wrong local heap signature: {:?}",
+                signature
+            )));
+        }
+        let version = input.read_u8()?;
+        if version != 0 {
+            return Err(Error::OxifiveError(format!(
+                "Unsupported local heap version: {}",
+                version
+            )));
+        }
+        let mut reserved = [0; 3];
+        input.read_exact(&mut reserved)?;
+        let data_segment_size = context.read_length(input)?;
+        let _offset_to_free_list = context.read_length(input)?;
+        Ok(LocalHeap {
+            data_segment_size,
+            address_of_data_segment: context.read_offset(input)?,
+        })
     }
 
-    pub fn object_name(&self, input: &mut impl ReadSeek, offset: u64) -> Result<String, Error> {
-        input.seek(SeekFrom::Start(self.address_of_data_segment + offset))?;
+    pub fn object_name(
+        &self,
+        input: &mut impl ReadSeek,
+        context: &FormatContext,
+        offset: u64,
+    ) -> Result<String, Error> {
+        if offset >= self.data_segment_size {
+            return Err(Error::OxifiveError(format!(
+                "Name offset {} is outside the local heap of size {}",
+                offset, self.data_segment_size
+            )));
+        }
+        context.seek(input, self.address_of_data_segment + offset)?;
         let mut end = 0;
         while input.read_u8()? != 0 {
             end += 1;
         }
-        input.seek(SeekFrom::Start(self.address_of_data_segment + offset))?;
+        context.seek(input, self.address_of_data_segment + offset)?;
         let mut result = vec![0; end];
         input.read_exact(&mut result)?;
         Ok(String::from_utf8(result)?)
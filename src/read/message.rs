@@ -1,4 +1,12 @@
+use crate::error::Error;
+use crate::read::context::FormatContext;
+use byteorder::ReadBytesExt;
 use num_enum::TryFromPrimitive;
+use std::io::Read;
+
+/// Message flag marking a message that is stored elsewhere, such as a
+/// committed datatype, and only referred to by the object header.
+pub const SHARED_MESSAGE_FLAG: u8 = 0b0000_0010;
 
 #[repr(u8)]
 #[derive(Clone, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -26,7 +34,8 @@ pub enum MessageType {
     DriverInfo = 0x0014,
     AttributeInfo = 0x0015,
     ObjectReferenceCount = 0x0016,
-    FileSpaceInfo = 0x0018,
+    FileSpaceInfo = 0x0017,
+    MetadataCacheImage = 0x0018,
 }
 
 #[derive(Clone, Debug)]
@@ -34,7 +43,6 @@ pub struct MessageHeaderV1 {
     pub message_type: MessageType,
     pub size: u16,
     pub flags: u8,
-    pub reserved: [u8; 3],
 }
 
 #[derive(Clone, Debug)]
@@ -43,3 +51,25 @@ pub struct MessageHeaderV2 {
     pub size: u16,
     pub flags: u8,
 }
+
+/// Reads a shared message and returns the address of the object header that
+/// holds the actual message.
+pub fn parse_shared_message_address(
+    input: &mut impl Read,
+    context: &FormatContext,
+) -> Result<u64, Error> {
+    let version = input.read_u8()?;
+    let shared_type = input.read_u8()?;
+    match (version, shared_type) {
+        (1, _) => {
+            let mut reserved = [0; 6];
+            input.read_exact(&mut reserved)?;
+            context.read_offset(input)
+        }
+        (2, _) | (3, 2) => context.read_offset(input),
+        _ => Err(Error::OxifiveError(format!(
+            "Unsupported shared message version {} of type {}",
+            version, shared_type
+        ))),
+    }
+}
@@ -1,9 +1,16 @@
+mod checksum;
+mod chunk_index;
+mod context;
+pub mod conversion;
 mod data_object;
 mod data_storage;
 pub mod dataset;
-mod dataspace;
-mod datatype;
+pub mod dataspace;
+pub mod datatype;
+pub mod decode;
+mod fill_value;
 mod filter_pipeline;
+pub mod global_heap;
 pub mod group;
 mod group_btree;
 pub mod io;
@@ -12,7 +19,8 @@ mod local_heap;
 mod message;
 mod node;
 pub mod object;
-mod superblock;
+pub mod reference;
+pub mod superblock;
 mod symbol_table;
 
 pub mod file;
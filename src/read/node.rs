@@ -1,9 +1,10 @@
 use crate::error::Error;
+use crate::read::context::FormatContext;
 use std::convert::TryFrom;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 use num_enum::TryFromPrimitive;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek};
 
 #[repr(u8)]
 #[derive(Clone, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -22,21 +23,17 @@ pub struct BTreeNodeKey {
 
 #[derive(Clone, Debug)]
 pub struct BTreeNode {
-    pub node_type: BTreeNodeType,
     pub node_level: u8,
-    pub entries_used: u16,
-    pub left_sibling: u64,
-    pub right_sibling: u64,
     pub keys: Vec<BTreeNodeKey>,
-    //pub addresses: Vec<u64>,
 }
 
 pub fn parse_node(
     input: &mut (impl Read + Seek + ?Sized),
+    context: &FormatContext,
     offset: u64,
     dimensions: usize,
 ) -> Result<BTreeNode, Error> {
-    input.seek(SeekFrom::Start(offset))?;
+    context.seek(input, offset)?;
     let signature = {
         let mut bytes = [0; 4];
         input.read_exact(&mut bytes)?;
@@ -56,11 +53,19 @@ pub fn parse_node(
     }
     let node_level = input.read_u8()?;
     let entries_used = input.read_u16::<LittleEndian>()?;
-    let left_sibling = input.read_u64::<LittleEndian>()?;
-    let right_sibling = input.read_u64::<LittleEndian>()?;
+    let max_entries = 2 * u32::from(context.chunk_btree_k);
+    if u32::from(entries_used) > max_entries {
+        return Err(Error::OxifiveError(format!(
+            "B-tree node at {} has {} entries, more than the 2K = {} allowed",
+            offset, entries_used, max_entries
+        )));
+    }
+    // Children are found through the addresses stored with each key, so the
+    // links to the sibling nodes of the same level are skipped
+    let _left_sibling = context.read_offset(input)?;
+    let _right_sibling = context.read_offset(input)?;
 
     let mut keys = vec![];
-    //let mut addresses = vec![];
 
     for _ in 0..entries_used {
         let chunk_size = input.read_u32::<LittleEndian>()?;
@@ -69,23 +74,14 @@ pub fn parse_node(
         for _ in 0..dimensions {
             chunk_offsets.push(input.read_u64::<LittleEndian>()?);
         }
-        let chunk_address = input.read_u64::<LittleEndian>()?;
+        let chunk_address = context.read_offset(input)?;
         keys.push(BTreeNodeKey {
             chunk_size,
             filter_mask,
             chunk_offsets,
             chunk_address,
         });
-        //addresses.push(chunk_address);
     }
 
-    Ok(BTreeNode {
-        node_type,
-        node_level,
-        entries_used,
-        left_sibling,
-        right_sibling,
-        keys,
-        //addresses,
-    })
+    Ok(BTreeNode { node_level, keys })
 }
@@ -1,6 +1,11 @@
+use std::sync::{Arc, Mutex};
+
 use crate::{
     error::Error,
-    read::{dataset::Dataset, group::Group},
+    read::{
+        context::FormatContext, conversion::ConversionPolicy, data_object::parse_data_object,
+        dataset::Dataset, datatype::Datatype, group::Group,
+    },
     ReadSeek,
 };
 
@@ -8,27 +13,63 @@ use crate::{
 pub enum Object<R> {
     Group(Group<R>),
     Dataset(Dataset<R>),
+    /// A committed datatype, which is stored in a group so that datasets can
+    /// share it.
+    Datatype(Datatype),
 }
 
 impl<R: ReadSeek> Object<R> {
+    /// Parses the object header at an address into a group, a dataset or a
+    /// committed datatype.
+    pub(crate) fn parse(
+        input: &Arc<Mutex<R>>,
+        context: &FormatContext,
+        address: u64,
+    ) -> Result<Object<R>, Error> {
+        let mut data_object = parse_data_object(&mut *input.lock().unwrap(), context, address)?;
+        if data_object.is_datatype() {
+            Ok(Object::Datatype(data_object.datatypes.remove(0)))
+        } else if data_object.is_group() {
+            Ok(Object::Group(Group {
+                data_object,
+                context: *context,
+                input: input.clone(),
+            }))
+        } else {
+            Ok(Object::Dataset(Dataset {
+                data_object,
+                context: *context,
+                input: input.clone(),
+                conversion: ConversionPolicy::default(),
+            }))
+        }
+    }
+
     pub fn object(&self, name: &str) -> Result<Object<R>, Error> {
         match self {
             Object::Group(group) => group.object(name),
-            _ => Err(Error::OxifiveError(format!("Not a group"))),
+            _ => Err(Error::OxifiveError("Not a group".to_string())),
         }
     }
 
     pub fn group(&self, name: &str) -> Result<Group<R>, Error> {
         match self {
             Object::Group(group) => group.group(name),
-            _ => Err(Error::OxifiveError(format!("Not a group"))),
+            _ => Err(Error::OxifiveError("Not a group".to_string())),
         }
     }
 
     pub fn dataset(&self, name: &str) -> Result<Dataset<R>, Error> {
         match self {
             Object::Group(group) => group.dataset(name),
-            _ => Err(Error::OxifiveError(format!("Not a group"))),
+            _ => Err(Error::OxifiveError("Not a group".to_string())),
+        }
+    }
+
+    pub fn datatype(&self, name: &str) -> Result<Datatype, Error> {
+        match self {
+            Object::Group(group) => group.datatype(name),
+            _ => Err(Error::OxifiveError("Not a group".to_string())),
         }
     }
 }
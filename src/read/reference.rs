@@ -0,0 +1,402 @@
+use crate::error::Error;
+use crate::read::{
+    context::UNDEFINED_ADDRESS,
+    dataset::DatatypeVerifiable,
+    datatype::{Datatype, DatatypeProperties, ReferenceKind},
+    decode::DecodeContext,
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use ndarray::Dimension;
+use num_enum::TryFromPrimitive;
+use std::convert::TryFrom;
+use std::io::{Cursor, Read};
+use std::ops::Range;
+
+/// A reference to an object, given by the address of its object header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ObjectReference {
+    pub address: u64,
+}
+
+impl ObjectReference {
+    /// Whether the reference was never set and points to no object.
+    pub fn is_null(&self) -> bool {
+        self.address == UNDEFINED_ADDRESS || self.address == 0
+    }
+}
+
+impl DatatypeVerifiable for ObjectReference {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        match datatype.properties {
+            DatatypeProperties::Reference {
+                kind: ReferenceKind::Object,
+            } => Ok(()),
+            _ => Err(Error::OxifiveError(format!(
+                "Wrong datatype found for ObjectReference: {:?}",
+                datatype
+            ))),
+        }
+    }
+
+    fn decode(
+        bytes: &[u8],
+        _datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        Ok(ObjectReference {
+            address: context
+                .format_context()
+                .read_offset(&mut Cursor::new(bytes))?,
+        })
+    }
+}
+
+/// A selection of elements in a dataspace, as stored by dataset region
+/// references.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selection {
+    None,
+    All,
+    /// Individual elements, in the order they were selected.
+    Points {
+        coordinates: Vec<Vec<u64>>,
+    },
+    /// A union of blocks.
+    Hyperslab {
+        blocks: Vec<HyperslabBlock>,
+    },
+    /// A regular hyperslab of `count` blocks of `block` elements in each
+    /// dimension, which start `stride` elements apart from `start`.
+    RegularHyperslab {
+        start: Vec<u64>,
+        stride: Vec<u64>,
+        count: Vec<u64>,
+        block: Vec<u64>,
+    },
+}
+
+/// A block of a hyperslab selection, from the start to the inclusive end
+/// coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HyperslabBlock {
+    pub start: Vec<u64>,
+    pub end: Vec<u64>,
+}
+
+impl Selection {
+    /// The selected elements of a dataspace of the given shape, as ranges of
+    /// their row-major indices. Hyperslab elements are given in row-major
+    /// order, with the elements shared by several blocks given once, and
+    /// points in the order they were selected.
+    pub fn ranges(&self, shape: &[u64]) -> Result<Vec<Range<u64>>, Error> {
+        match self {
+            Selection::None => Ok(vec![]),
+            Selection::All => match shape.iter().product() {
+                0 => Ok(vec![]),
+                size => Ok(vec![Range {
+                    start: 0,
+                    end: size,
+                }]),
+            },
+            Selection::Points { coordinates } => {
+                let mut ranges: Vec<Range<u64>> = vec![];
+                for coordinates in coordinates {
+                    let index = element_index(coordinates, shape)?;
+                    match ranges.last_mut() {
+                        Some(range) if range.end == index => range.end += 1,
+                        _ => ranges.push(index..index + 1),
+                    }
+                }
+                Ok(ranges)
+            }
+            Selection::Hyperslab { blocks } => {
+                let mut ranges = vec![];
+                for block in blocks {
+                    push_block_ranges(&block.start, &block.end, shape, &mut ranges)?;
+                }
+                Ok(merge_ranges(ranges))
+            }
+            Selection::RegularHyperslab {
+                start,
+                stride,
+                count,
+                block,
+            } => {
+                let mut ranges = vec![];
+                let count_shape: Vec<usize> = count.iter().map(|&x| x as usize).collect();
+                for index in ndarray::indices(count_shape) {
+                    let (block_start, block_end) =
+                        regular_hyperslab_block(start, stride, block, index.slice())?;
+                    push_block_ranges(&block_start, &block_end, shape, &mut ranges)?;
+                }
+                Ok(merge_ranges(ranges))
+            }
+        }
+    }
+}
+
+/// The row-major index of a selected element.
+fn element_index(coordinates: &[u64], shape: &[u64]) -> Result<u64, Error> {
+    if coordinates.len() != shape.len()
+        || coordinates.iter().zip(shape).any(|(&x, &size)| x >= size)
+    {
+        return Err(Error::OxifiveError(format!(
+            "Selected element {:?} is outside the dataset shape {:?}",
+            coordinates, shape
+        )));
+    }
+    Ok(coordinates
+        .iter()
+        .zip(shape)
+        .fold(0, |index, (&x, &size)| index * size + x))
+}
+
+/// Adds the elements of a block, from the start to the inclusive end
+/// coordinates, as one range for each of its rows along the last dimension.
+fn push_block_ranges(
+    start: &[u64],
+    end: &[u64],
+    shape: &[u64],
+    ranges: &mut Vec<Range<u64>>,
+) -> Result<(), Error> {
+    let inside = !shape.is_empty()
+        && start.len() == shape.len()
+        && end.len() == shape.len()
+        && (0..shape.len()).all(|dimension| {
+            start[dimension] <= end[dimension] && end[dimension] < shape[dimension]
+        });
+    if !inside {
+        return Err(Error::OxifiveError(format!(
+            "Selected block from {:?} to {:?} is outside the dataset shape {:?}",
+            start, end, shape
+        )));
+    }
+    let last = shape.len() - 1;
+    let rows: Vec<usize> = (0..last)
+        .map(|dimension| (end[dimension] - start[dimension] + 1) as usize)
+        .collect();
+    for row in ndarray::indices(rows) {
+        let first = (0..shape.len()).fold(0, |index, dimension| {
+            let offset = if dimension < last { row[dimension] } else { 0 };
+            index * shape[dimension] + start[dimension] + offset as u64
+        });
+        ranges.push(first..first + end[last] - start[last] + 1);
+    }
+    Ok(())
+}
+
+/// Sorts ranges and merges those that overlap or touch.
+fn merge_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// A reference to a selection of elements in a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionReference {
+    pub dataset: ObjectReference,
+    pub selection: Selection,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+enum SelectionType {
+    None = 0,
+    Points = 1,
+    Hyperslab = 2,
+    All = 3,
+}
+
+/// Reads a coordinate or count, which is stored in `size` bytes.
+fn read_selection_value(input: &mut impl Read, size: usize) -> Result<u64, Error> {
+    Ok(input.read_uint::<LittleEndian>(size)?)
+}
+
+/// Reads the number of bytes that the values of a selection are stored in.
+fn read_value_size(input: &mut impl Read) -> Result<usize, Error> {
+    match input.read_u8()? {
+        size @ 1..=8 => Ok(size as usize),
+        size => Err(Error::OxifiveError(format!(
+            "Invalid size of selection values: {}",
+            size
+        ))),
+    }
+}
+
+fn read_coordinates(input: &mut impl Read, rank: u32, size: usize) -> Result<Vec<u64>, Error> {
+    (0..rank)
+        .map(|_| read_selection_value(input, size))
+        .collect()
+}
+
+fn parse_points(input: &mut impl Read, version: u32) -> Result<Selection, Error> {
+    let size = match version {
+        1 => {
+            let _reserved = input.read_u32::<LittleEndian>()?;
+            let _length = input.read_u32::<LittleEndian>()?;
+            4
+        }
+        2 => read_value_size(input)?,
+        _ => {
+            return Err(Error::OxifiveError(format!(
+                "Unsupported point selection version {}",
+                version
+            )))
+        }
+    };
+    let rank = input.read_u32::<LittleEndian>()?;
+    let count = read_selection_value(input, size)?;
+    let coordinates = (0..count)
+        .map(|_| read_coordinates(input, rank, size))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Selection::Points { coordinates })
+}
+
+/// The start and inclusive end coordinates of a block of a regular
+/// hyperslab, given by its index in the count of each dimension.
+fn regular_hyperslab_block(
+    start: &[u64],
+    stride: &[u64],
+    block: &[u64],
+    index: &[usize],
+) -> Result<(Vec<u64>, Vec<u64>), Error> {
+    let block_start = (0..start.len())
+        .map(|dimension| {
+            (index[dimension] as u64)
+                .checked_mul(stride[dimension])?
+                .checked_add(start[dimension])
+        })
+        .collect::<Option<Vec<u64>>>();
+    let block_end = block_start.as_ref().and_then(|block_start| {
+        block_start
+            .iter()
+            .zip(block)
+            .map(|(&start, &size)| start.checked_add(size - 1))
+            .collect::<Option<Vec<u64>>>()
+    });
+    match (block_start, block_end) {
+        (Some(start), Some(end)) => Ok((start, end)),
+        _ => Err(Error::OxifiveError(format!(
+            "Hyperslab block {:?} exceeds the range of coordinates",
+            index
+        ))),
+    }
+}
+
+fn parse_hyperslab(input: &mut impl Read, version: u32) -> Result<Selection, Error> {
+    let (regular, size) = match version {
+        1 => {
+            let _reserved = input.read_u32::<LittleEndian>()?;
+            let _length = input.read_u32::<LittleEndian>()?;
+            (false, 4)
+        }
+        2 => {
+            let flags = input.read_u8()?;
+            let _length = input.read_u32::<LittleEndian>()?;
+            (flags & 0b1 != 0, 8)
+        }
+        3 => {
+            let flags = input.read_u8()?;
+            (flags & 0b1 != 0, read_value_size(input)?)
+        }
+        _ => {
+            return Err(Error::OxifiveError(format!(
+                "Unsupported hyperslab selection version {}",
+                version
+            )))
+        }
+    };
+    let rank = input.read_u32::<LittleEndian>()?;
+    if regular {
+        let mut parameters = [vec![], vec![], vec![], vec![]];
+        for _ in 0..rank {
+            for values in parameters.iter_mut() {
+                values.push(read_selection_value(input, size)?);
+            }
+        }
+        let [start, stride, count, block] = parameters;
+        // Unlimited counts and blocks are stored as all ones in the size of
+        // the values
+        let unlimited = u64::MAX >> (64 - 8 * size);
+        if count.contains(&unlimited) || block.contains(&unlimited) {
+            return Err(Error::OxifiveError(
+                "Unlimited hyperslab selections are not supported".to_string(),
+            ));
+        }
+        if block.contains(&0) {
+            return Err(Error::OxifiveError(
+                "Hyperslab selections with empty blocks are not supported".to_string(),
+            ));
+        }
+        return Ok(Selection::RegularHyperslab {
+            start,
+            stride,
+            count,
+            block,
+        });
+    }
+    let count = read_selection_value(input, size)?;
+    let blocks = (0..count)
+        .map(|_| {
+            Ok(HyperslabBlock {
+                start: read_coordinates(input, rank, size)?,
+                end: read_coordinates(input, rank, size)?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Selection::Hyperslab { blocks })
+}
+
+/// Parses a selection as serialized in the global heap objects of dataset
+/// region references.
+fn parse_selection(input: &mut impl Read) -> Result<Selection, Error> {
+    let selection_type = SelectionType::try_from(input.read_u32::<LittleEndian>()?)?;
+    let version = input.read_u32::<LittleEndian>()?;
+    match selection_type {
+        SelectionType::Points => parse_points(input, version),
+        SelectionType::Hyperslab => parse_hyperslab(input, version),
+        SelectionType::None => Ok(Selection::None),
+        SelectionType::All => Ok(Selection::All),
+    }
+}
+
+impl DatatypeVerifiable for RegionReference {
+    fn verify(datatype: &Datatype) -> Result<(), Error> {
+        match datatype.properties {
+            DatatypeProperties::Reference {
+                kind: ReferenceKind::DatasetRegion,
+            } => Ok(()),
+            _ => Err(Error::OxifiveError(format!(
+                "Wrong datatype found for RegionReference: {:?}",
+                datatype
+            ))),
+        }
+    }
+
+    /// Region references hold the global heap ID of the referenced dataset's
+    /// address followed by the serialized selection.
+    fn decode(
+        bytes: &[u8],
+        _datatype: &Datatype,
+        context: &mut DecodeContext,
+    ) -> Result<Self, Error> {
+        let format_context = context.format_context();
+        let mut cursor = Cursor::new(bytes);
+        let collection_address = format_context.read_offset(&mut cursor)?;
+        let index = cursor.read_u32::<LittleEndian>()?;
+        let mut object = Cursor::new(context.heap().object(collection_address, index)?);
+        Ok(RegionReference {
+            dataset: ObjectReference {
+                address: format_context.read_offset(&mut object)?,
+            },
+            selection: parse_selection(&mut object)?,
+        })
+    }
+}
@@ -1,6 +1,17 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use crate::read::{
+    checksum::verify_checksum,
+    context::{FormatContext, UNDEFINED_ADDRESS},
+    symbol_table::SymbolTableEntry,
+};
 use crate::{error::Error, ReadSeek};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read, SeekFrom};
 
+pub const FORMAT_SIGNATURE: [u8; 8] = [137, 72, 68, 70, 13, 10, 26, 10];
+
+/// Superblock versions 0 and 1 share this layout. Version 1 adds the
+/// indexed storage internal node K and a reserved field before the base
+/// address.
 #[derive(Clone, Debug)]
 pub struct SuperBlockVersion0 {
     pub format_signature: [u8; 8],
@@ -15,16 +26,197 @@ pub struct SuperBlockVersion0 {
     pub group_leaf_node_k: u16,
     pub group_internal_node_k: u16,
     pub file_consistency_flags: u32,
+    pub indexed_storage_internal_node_k: Option<u16>,
+    pub reserved_2: Option<u16>,
     pub base_address: u64,
     pub free_space_address: u64,
     pub end_of_file_address: u64,
     pub driver_information_address: u64,
+    pub root_group_symbol_table_entry: SymbolTableEntry,
+}
+
+/// Superblock versions 2 and 3 share this layout. Version 3 only adds
+/// meaning to the file consistency flags, which are used for SWMR access.
+#[derive(Clone, Debug)]
+pub struct SuperBlockVersion2 {
+    pub format_signature: [u8; 8],
+    pub superblock_version: u8,
+    pub offset_size: u8,
+    pub length_size: u8,
+    pub file_consistency_flags: u8,
+    pub base_address: u64,
+    pub superblock_extension_address: Option<u64>,
+    pub end_of_file_address: u64,
+    pub root_group_object_header_address: u64,
+    pub checksum: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct BTreeKValues {
+    pub indexed_storage_internal_node_k: u16,
+    pub group_internal_node_k: u16,
+    pub group_leaf_node_k: u16,
+}
+
+#[derive(Clone, Debug)]
+pub enum SuperBlock {
+    Version0(SuperBlockVersion0),
+    Version2(SuperBlockVersion2),
+}
+
+impl SuperBlock {
+    pub fn version(&self) -> u8 {
+        match self {
+            SuperBlock::Version0(superblock) => superblock.superblock_version,
+            SuperBlock::Version2(superblock) => superblock.superblock_version,
+        }
+    }
+
+    pub fn root_group_address(&self) -> u64 {
+        match self {
+            SuperBlock::Version0(superblock) => {
+                superblock
+                    .root_group_symbol_table_entry
+                    .object_header_address
+            }
+            SuperBlock::Version2(superblock) => superblock.root_group_object_header_address,
+        }
+    }
+
+    pub fn offset_size(&self) -> u8 {
+        match self {
+            SuperBlock::Version0(superblock) => superblock.offset_size,
+            SuperBlock::Version2(superblock) => superblock.offset_size,
+        }
+    }
+
+    pub fn length_size(&self) -> u8 {
+        match self {
+            SuperBlock::Version0(superblock) => superblock.length_size,
+            SuperBlock::Version2(superblock) => superblock.length_size,
+        }
+    }
+
+    pub fn superblock_extension_address(&self) -> Option<u64> {
+        match self {
+            SuperBlock::Version0(_) => None,
+            SuperBlock::Version2(superblock) => superblock.superblock_extension_address,
+        }
+    }
+}
+
+fn parse_superblock_version_0(
+    input: &mut impl ReadSeek,
+    format_signature: [u8; 8],
+    superblock_version: u8,
+) -> Result<SuperBlockVersion0, Error> {
+    let free_storage_version = input.read_u8()?;
+    let root_group_version = input.read_u8()?;
+    let reserved_0 = input.read_u8()?;
+    let shared_header_version = input.read_u8()?;
+    let offset_size = input.read_u8()?;
+    let length_size = input.read_u8()?;
+    let reserved_1 = input.read_u8()?;
+    let group_leaf_node_k = input.read_u16::<LittleEndian>()?;
+    let group_internal_node_k = input.read_u16::<LittleEndian>()?;
+    let file_consistency_flags = input.read_u32::<LittleEndian>()?;
+    let (indexed_storage_internal_node_k, reserved_2) = if superblock_version == 1 {
+        (
+            Some(input.read_u16::<LittleEndian>()?),
+            Some(input.read_u16::<LittleEndian>()?),
+        )
+    } else {
+        (None, None)
+    };
+    let context = FormatContext::new(offset_size, length_size)?;
+    Ok(SuperBlockVersion0 {
+        format_signature,
+        superblock_version,
+        free_storage_version,
+        root_group_version,
+        reserved_0,
+        shared_header_version,
+        offset_size,
+        length_size,
+        reserved_1,
+        group_leaf_node_k,
+        group_internal_node_k,
+        file_consistency_flags,
+        indexed_storage_internal_node_k,
+        reserved_2,
+        base_address: context.read_offset(input)?,
+        free_space_address: context.read_offset(input)?,
+        end_of_file_address: context.read_offset(input)?,
+        driver_information_address: context.read_offset(input)?,
+        root_group_symbol_table_entry: SymbolTableEntry::read(input, &context)?,
+    })
+}
+
+fn parse_superblock_version_2(
+    input: &mut impl ReadSeek,
+    format_signature: [u8; 8],
+    superblock_version: u8,
+) -> Result<SuperBlockVersion2, Error> {
+    // The checksum covers every byte before it, so the fixed-size part of the
+    // superblock is read in full before parsing the individual fields.
+    let mut sizes_and_flags = [0; 3];
+    input.read_exact(&mut sizes_and_flags)?;
+    let [offset_size, length_size, file_consistency_flags] = sizes_and_flags;
+    let context = FormatContext::new(offset_size, length_size)?;
+    let mut addresses = vec![0; 4 * offset_size as usize];
+    input.read_exact(&mut addresses)?;
+    let checksum = input.read_u32::<LittleEndian>()?;
+
+    let mut bytes = format_signature.to_vec();
+    bytes.push(superblock_version);
+    bytes.extend_from_slice(&sizes_and_flags);
+    bytes.extend_from_slice(&addresses);
+    verify_checksum(&bytes, checksum, "superblock")?;
+
+    let mut cursor = Cursor::new(addresses);
+    let base_address = context.read_offset(&mut cursor)?;
+    let superblock_extension_address = match context.read_offset(&mut cursor)? {
+        UNDEFINED_ADDRESS => None,
+        address => Some(address),
+    };
+    Ok(SuperBlockVersion2 {
+        format_signature,
+        superblock_version,
+        offset_size,
+        length_size,
+        file_consistency_flags,
+        base_address,
+        superblock_extension_address,
+        end_of_file_address: context.read_offset(&mut cursor)?,
+        root_group_object_header_address: context.read_offset(&mut cursor)?,
+        checksum,
+    })
 }
 
-pub fn parse_superblock<R: ReadSeek>(input: &mut R) -> Result<SuperBlockVersion0, Error> {
+/// Finds the position of the superblock, which is either at the beginning of
+/// the file or after a userblock at 512, 1024, 2048 and further powers of
+/// two bytes into the file.
+pub fn find_superblock<R: ReadSeek>(input: &mut R) -> Result<u64, Error> {
+    let file_size = input.seek(SeekFrom::End(0))?;
+    let mut position = 0;
+    while position + FORMAT_SIGNATURE.len() as u64 <= file_size {
+        input.seek(SeekFrom::Start(position))?;
+        let mut signature = [0; 8];
+        input.read_exact(&mut signature)?;
+        if signature == FORMAT_SIGNATURE {
+            return Ok(position);
+        }
+        position = if position == 0 { 512 } else { position * 2 };
+    }
+    Err(Error::OxifiveError(
+        "Could not find the HDF5 format signature".to_string(),
+    ))
+}
+
+pub fn parse_superblock<R: ReadSeek>(input: &mut R) -> Result<SuperBlock, Error> {
     let mut format_signature = [0; 8];
     input.read_exact(&mut format_signature)?;
-    if format_signature != [137, 72, 68, 70, 13, 10, 26, 10] {
+    if format_signature != FORMAT_SIGNATURE {
         return Err(Error::OxifiveError(format!(
             "Wrong header, found {:#?}",
             format_signature
@@ -32,29 +224,35 @@ pub fn parse_superblock<R: ReadSeek>(input: &mut R) -> Result<SuperBlockVersion0
     }
 
     let superblock_version = input.read_u8()?;
-    if superblock_version != 0 {
-        return Err(Error::OxifiveError(format!(
-            "Only superblock version 0 is supported, but found {}",
+    match superblock_version {
+        0 | 1 => Ok(SuperBlock::Version0(parse_superblock_version_0(
+            input,
+            format_signature,
+            superblock_version,
+        )?)),
+        2 | 3 => Ok(SuperBlock::Version2(parse_superblock_version_2(
+            input,
+            format_signature,
+            superblock_version,
+        )?)),
+        _ => Err(Error::OxifiveError(format!(
+            "Unsupported superblock version {}",
             superblock_version
-        )));
+        ))),
     }
+}
 
-    Ok(SuperBlockVersion0 {
-        format_signature,
-        superblock_version,
-        free_storage_version: input.read_u8()?,
-        root_group_version: input.read_u8()?,
-        reserved_0: input.read_u8()?,
-        shared_header_version: input.read_u8()?,
-        offset_size: input.read_u8()?,
-        length_size: input.read_u8()?,
-        reserved_1: input.read_u8()?,
-        group_leaf_node_k: input.read_u16::<LittleEndian>()?,
+pub fn parse_btree_k_values_message(input: &mut impl Read) -> Result<BTreeKValues, Error> {
+    let version = input.read_u8()?;
+    if version != 0 {
+        return Err(Error::OxifiveError(format!(
+            "Unsupported B-tree 'K' values message version {}",
+            version
+        )));
+    }
+    Ok(BTreeKValues {
+        indexed_storage_internal_node_k: input.read_u16::<LittleEndian>()?,
         group_internal_node_k: input.read_u16::<LittleEndian>()?,
-        file_consistency_flags: input.read_u32::<LittleEndian>()?,
-        base_address: input.read_u64::<LittleEndian>()?,
-        free_space_address: input.read_u64::<LittleEndian>()?,
-        end_of_file_address: input.read_u64::<LittleEndian>()?,
-        driver_information_address: input.read_u64::<LittleEndian>()?,
+        group_leaf_node_k: input.read_u16::<LittleEndian>()?,
     })
 }
@@ -1,12 +1,10 @@
 use crate::error::Error;
+use crate::read::context::FormatContext;
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::Read;
 
 #[derive(Clone, Debug)]
 pub struct SymbolTableNode {
-    pub signature: [u8; 4],
-    pub version: u8,
-    pub reserved: u8,
     pub symbols: u16,
 }
 
@@ -21,24 +19,33 @@ pub struct SymbolTableEntry {
 
 impl SymbolTableNode {
     pub fn read(input: &mut impl Read) -> Result<Self, Error> {
+        let mut signature = [0; 4];
+        input.read_exact(&mut signature)?;
+        if &signature != b"SNOD" {
+            return Err(Error::OxifiveError(format!(
+                "Wrong symbol table node signature: {:?}",
+                signature
+            )));
+        }
+        let version = input.read_u8()?;
+        if version != 1 {
+            return Err(Error::OxifiveError(format!(
+                "Unsupported symbol table node version: {}",
+                version
+            )));
+        }
+        let _reserved = input.read_u8()?;
         Ok(SymbolTableNode {
-            signature: {
-                let mut result = [0; 4];
-                input.read_exact(&mut result)?;
-                result
-            },
-            version: input.read_u8()?,
-            reserved: input.read_u8()?,
             symbols: input.read_u16::<LittleEndian>()?,
         })
     }
 }
 
 impl SymbolTableEntry {
-    pub fn read(input: &mut impl Read) -> Result<SymbolTableEntry, Error> {
+    pub fn read(input: &mut impl Read, context: &FormatContext) -> Result<SymbolTableEntry, Error> {
         Ok(SymbolTableEntry {
-            link_name_offset: input.read_u64::<LittleEndian>()?,
-            object_header_address: input.read_u64::<LittleEndian>()?,
+            link_name_offset: context.read_length(input)?,
+            object_header_address: context.read_offset(input)?,
             cache_type: input.read_u32::<LittleEndian>()?,
             reserved: input.read_u32::<LittleEndian>()?,
             scratch: {
@@ -0,0 +1,5 @@
+/// Opens one of the test files in `tests/files`.
+pub fn open(filename: &str) -> Result<oxifive::FileReader<std::fs::File>, oxifive::error::Error> {
+    let input = std::fs::File::open(filename).unwrap();
+    oxifive::FileReader::new(input)
+}
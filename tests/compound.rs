@@ -0,0 +1,127 @@
+use ndarray::{array, Ix1, Ix2};
+use oxifive::H5Type;
+
+mod common;
+use common::open;
+
+#[derive(Clone, Debug, PartialEq, H5Type)]
+struct Record {
+    id: i32,
+    value: f64,
+    #[oxifive(name = "label name")]
+    label: String,
+}
+
+#[derive(Clone, Debug, PartialEq, H5Type)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, H5Type)]
+struct Labelled {
+    id: i16,
+    point: Point,
+}
+
+#[derive(Clone, Debug, PartialEq, H5Type)]
+struct Missing {
+    id: i32,
+    missing: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, H5Type)]
+struct Particle {
+    id: i32,
+    position: [f32; 3],
+}
+
+#[test]
+fn read_compound_versions() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/compound.h5")?;
+    let expected = vec![
+        Record {
+            id: 1,
+            value: 0.5,
+            label: "first".to_string(),
+        },
+        Record {
+            id: 2,
+            value: -1.5,
+            label: "second".to_string(),
+        },
+        Record {
+            id: 3,
+            value: 1e10,
+            label: "".to_string(),
+        },
+    ];
+    for name in &["records_v1", "records_v2", "records_v3"] {
+        let records = file.dataset(name)?.read::<Record, Ix1>()?;
+        assert!(records.to_vec() == expected);
+    }
+    assert!(file.dataset("records_v3")?.read::<Missing, Ix1>().is_err());
+    assert!(file.dataset("records_v3")?.read::<f64, Ix1>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_nested_compound() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/compound.h5")?;
+    let nested = file.dataset("nested")?.read::<Labelled, Ix1>()?;
+    assert!(
+        nested.to_vec()
+            == vec![
+                Labelled {
+                    id: 7,
+                    point: Point { x: 1.0, y: 2.0 },
+                },
+                Labelled {
+                    id: -8,
+                    point: Point { x: 3.0, y: 4.0 },
+                },
+            ]
+    );
+    Ok(())
+}
+
+#[test]
+fn read_compound_fields() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/compound.h5")?;
+    let records = file.dataset("records_v2")?;
+    let ids = records.read_field::<i32>("id")?;
+    assert!(ids.into_dimensionality::<Ix1>()? == array![1, 2, 3]);
+    let columns = records.read_fields::<f64>(&["weight", "value"])?;
+    assert!(columns.len() == 2);
+    assert!(columns[0].clone().into_dimensionality::<Ix1>()? == array![1.0, -3.0, 2e10]);
+    assert!(columns[1].clone().into_dimensionality::<Ix1>()? == array![0.5, -1.5, 1e10]);
+    let points = file.dataset("nested")?.read_field::<Point>("point")?;
+    assert!(points[[1]] == Point { x: 3.0, y: 4.0 });
+    let converted_ids = records.read_field::<f64>("id")?;
+    assert!(converted_ids.into_dimensionality::<Ix1>()? == array![1.0, 2.0, 3.0]);
+    assert!(records.read_field::<String>("id").is_err());
+    assert!(records.read_field::<i32>("missing").is_err());
+    assert!(records.read_fields::<i32>(&["id", "value"]).is_err());
+    Ok(())
+}
+
+#[test]
+fn read_compound_array_members() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/arrays.h5")?;
+    for name in &["compound_v1", "compound_v3"] {
+        let dataset = file.dataset(name)?;
+        let positions = dataset.read_field::<f32>("position")?;
+        assert!(
+            positions.into_dimensionality::<Ix2>()? == array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]
+        );
+        let particles = dataset.read::<Particle, Ix1>()?;
+        assert!(
+            particles[[1]]
+                == Particle {
+                    id: 2,
+                    position: [4.0, 5.0, 6.0],
+                }
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use ndarray::{array, Ix0, Ix1, Ix2, IxDyn};
+use oxifive::read::dataspace::Dataspace;
+
+mod common;
+use common::open;
+
+#[test]
+fn read_scalar_dataspaces() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/dataspaces.h5")?;
+    let scalar = file.dataset("scalar")?;
+    assert!(scalar.dataspace() == Dataspace::Scalar);
+    assert!(scalar.shape().is_empty());
+    assert!(scalar.read_scalar::<f64>()? == 2.5);
+    assert!(scalar.read::<f64, Ix0>()?.into_scalar() == 2.5);
+    assert!(file.dataset("scalar_v1")?.read_scalar::<i32>()? == 42);
+    assert!(file.dataset("vector")?.read_scalar::<i32>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_null_dataspaces() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/dataspaces.h5")?;
+    let empty = file.dataset("empty")?;
+    assert!(empty.dataspace() == Dataspace::Null);
+    assert!(empty.read::<f32, Ix1>()?.is_empty());
+    assert!(empty.read::<f32, Ix2>()?.shape() == [0, 0]);
+    assert!(empty.read::<f32, IxDyn>()?.is_empty());
+    assert!(empty.read::<f32, Ix0>().is_err());
+    assert!(empty.read_scalar::<f32>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_maximum_dimensions() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/dataspaces.h5")?;
+    let appendable = file.dataset("appendable")?;
+    assert!(appendable.shape() == vec![2, 3]);
+    assert!(appendable.maxshape() == vec![None, Some(3)]);
+    assert!(appendable.read::<i16, Ix2>()? == array![[1, 2, 3], [4, 5, 6]]);
+    let bounded = file.dataset("bounded_v1")?;
+    assert!(bounded.maxshape() == vec![Some(10)]);
+    assert!(bounded.read::<i32, Ix1>()? == array![7, 8]);
+    assert!(file.dataset("vector")?.maxshape() == vec![Some(2)]);
+    assert!(file.dataset("scalar")?.maxshape().is_empty());
+    Ok(())
+}
@@ -0,0 +1,335 @@
+use ndarray::{array, Ix1, Ix2, Ix3};
+use oxifive::read::conversion::ConversionPolicy;
+use oxifive::read::datatype::ByteOrder;
+use oxifive::H5Type;
+
+mod common;
+use common::open;
+
+#[test]
+fn read_big_endian() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/big_endian.h5")?;
+    let float32 = file.dataset("float32")?;
+    assert!(float32.datatype().byte_order() == ByteOrder::BigEndian);
+    assert!(float32.read::<f32, Ix2>()? == array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    let float64 = file.dataset("float64")?.read::<f64, Ix1>()?;
+    assert!(float64 == array![0.5, -1.25, 1e300]);
+    let chunked = file.dataset("chunked")?.read::<f32, Ix2>()?;
+    assert!(chunked[[1, 2]] == 6.0);
+    Ok(())
+}
+
+#[test]
+fn read_integers() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/integers.h5")?;
+    assert!(file.dataset("i8")?.read::<i8, Ix1>()? == array![-5, 0, 7]);
+    assert!(file.dataset("i16")?.read::<i16, Ix1>()? == array![-5, 0, 7]);
+    assert!(file.dataset("i32")?.read::<i32, Ix1>()? == array![-5, 0, 7]);
+    assert!(file.dataset("i64")?.read::<i64, Ix1>()? == array![-5, 0, 7]);
+    assert!(file.dataset("u8")?.read::<u8, Ix1>()? == array![5, 0, u8::MAX]);
+    assert!(file.dataset("u16")?.read::<u16, Ix1>()? == array![5, 0, u16::MAX]);
+    assert!(file.dataset("u32")?.read::<u32, Ix1>()? == array![5, 0, u32::MAX]);
+    assert!(file.dataset("u64")?.read::<u64, Ix1>()? == array![5, 0, u64::MAX]);
+    assert!(file.dataset("packed12")?.read::<i16, Ix1>()? == array![-3, 100, 2047]);
+    assert!(file.dataset("bool")?.read::<bool, Ix1>()? == array![false, true, true, false]);
+    assert!(file.dataset("i8")?.read::<u8, Ix1>().is_err());
+    assert!(file.dataset("u32")?.read::<i32, Ix1>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_with_conversion() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/integers.h5")?;
+    assert!(file.dataset("i16")?.read::<f64, Ix1>()? == array![-5.0, 0.0, 7.0]);
+    assert!(file.dataset("u8")?.read::<i64, Ix1>()? == array![5, 0, 255]);
+    let saturated = file
+        .dataset("i8")?
+        .with_conversion(ConversionPolicy::Saturate);
+    assert!(saturated.read::<u8, Ix1>()? == array![0, 0, 7]);
+    let wrapped = file.dataset("i8")?.with_conversion(ConversionPolicy::Wrap);
+    assert!(wrapped.read::<u8, Ix1>()? == array![251, 0, 7]);
+    let saturated = file
+        .dataset("u32")?
+        .with_conversion(ConversionPolicy::Saturate);
+    assert!(saturated.read::<i32, Ix1>()? == array![5, 0, i32::MAX]);
+    let wrapped = file.dataset("u32")?.with_conversion(ConversionPolicy::Wrap);
+    assert!(wrapped.read::<i16, Ix1>()? == array![5, 0, -1]);
+    let u64 = file.dataset("u64")?;
+    assert!(u64.read::<f64, Ix1>().is_err());
+    let rounded = u64
+        .with_conversion(ConversionPolicy::Saturate)
+        .read::<f64, Ix1>()?;
+    assert!(rounded == array![5.0, 0.0, 2f64.powi(64)]);
+
+    let file = open("tests/files/big_endian.h5")?;
+    let float64 = file.dataset("float64")?;
+    assert!(float64.read::<f32, Ix1>().is_err());
+    assert!(float64.read::<i32, Ix1>().is_err());
+    let saturated = float64.with_conversion(ConversionPolicy::Saturate);
+    assert!(saturated.read::<f32, Ix1>()? == array![0.5, -1.25, f32::MAX]);
+    assert!(saturated.read::<i32, Ix1>()? == array![0, -1, i32::MAX]);
+    let wrapped = file
+        .dataset("float64")?
+        .with_conversion(ConversionPolicy::Wrap);
+    assert!(wrapped.read::<f32, Ix1>()?[2] == f32::INFINITY);
+    assert!(file.dataset("float32")?.read::<f64, Ix2>()?[[1, 2]] == 6.0);
+    Ok(())
+}
+
+#[test]
+fn read_custom_floats() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/floats.h5")?;
+    let expected = array![1.0, -2.5, 65504.0, 2f32.powi(-24)];
+    assert!(file.dataset("f16")?.read::<f32, Ix1>()? == expected);
+    assert!(file.dataset("f16_be")?.read::<f64, Ix1>()? == expected.mapv(f64::from));
+    let special = file.dataset("f16_special")?.read::<f32, Ix1>()?;
+    assert!(special[0] == f32::INFINITY && special[1].is_nan());
+    assert!(file.dataset("f24")?.read::<f32, Ix1>()? == array![1.5, -0.25, 3.0]);
+    assert!(file.dataset("f24")?.read::<i32, Ix1>().is_err());
+    Ok(())
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn read_half() -> Result<(), oxifive::error::Error> {
+    use half::f16;
+    let file = open("tests/files/floats.h5")?;
+    let expected = array![1.0, -2.5, 65504.0, 2f32.powi(-24)].mapv(f16::from_f32);
+    assert!(file.dataset("f16")?.read::<f16, Ix1>()? == expected);
+    assert!(file.dataset("f16_be")?.read::<f16, Ix1>()? == expected);
+    let f24 = file.dataset("f24")?.read::<f16, Ix1>()?;
+    assert!(f24 == array![1.5, -0.25, 3.0].mapv(f16::from_f32));
+    Ok(())
+}
+
+#[cfg(feature = "num-complex")]
+#[test]
+fn read_complex() -> Result<(), oxifive::error::Error> {
+    use num_complex::{Complex32, Complex64};
+    let file = open("tests/files/complex.h5")?;
+    let fft = file.dataset("fft")?.read::<Complex64, Ix2>()?;
+    assert!(fft[[0, 1]] == Complex64::new(0.0, 1.0));
+    assert!(fft[[1, 1]] == Complex64::new(2.0, -2.0));
+    let octave = file.dataset("octave")?.read::<Complex32, Ix1>()?;
+    assert!(octave == array![Complex32::new(1.5, -1.0), Complex32::new(0.0, 3.0)]);
+    assert!(file.dataset("octave")?.read::<Complex64, Ix1>()?[0] == Complex64::new(1.5, -1.0));
+    assert!(file.dataset("fft")?.read::<Complex32, Ix2>()?[[1, 1]] == Complex32::new(2.0, -2.0));
+    assert!(file
+        .dataset("not_complex")?
+        .read::<Complex32, Ix1>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn read_fixed_length_strings() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/strings.h5")?;
+    let null_terminated = file.dataset("null_terminated")?.read_strings::<Ix1>()?;
+    assert!(null_terminated == array!["x", "hello", "abcdef"].mapv(String::from));
+    let null_padded = file.dataset("null_padded")?.read::<String, Ix2>()?;
+    assert!(null_padded == array![["a", "bc"], ["def", "ghij"]].mapv(String::from));
+    let space_padded = file.dataset("space_padded")?.read_strings::<Ix1>()?;
+    assert!(space_padded == array!["ab", "  cd"].mapv(String::from));
+    let utf8 = file.dataset("utf8")?.read_strings::<Ix1>()?;
+    assert!(utf8 == array!["été", "日本"].mapv(String::from));
+    assert!(file.dataset("not_ascii")?.read_strings::<Ix1>().is_err());
+    assert!(file.dataset("utf8")?.read::<u8, Ix1>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_variable_length_strings() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/vlen_strings.h5")?;
+    let contiguous = file.dataset("contiguous")?.read_strings::<Ix1>()?;
+    let long = "longer string spanning more than eight bytes";
+    assert!(contiguous == array!["a", "", "héllo wörld", long, "a"].mapv(String::from));
+    // The last chunk is never written and holds empty strings
+    let chunked = file.dataset("chunked")?.read::<String, Ix1>()?;
+    assert!(chunked == array!["zero", "one", "two", "three", ""].mapv(String::from));
+    assert!(file.dataset("chunked")?.read::<u8, Ix1>().is_err());
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, H5Type)]
+enum Color {
+    #[oxifive(name = "RED")]
+    Red,
+    #[oxifive(name = "GREEN")]
+    Green,
+    #[oxifive(name = "BLUE")]
+    Blue,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, H5Type)]
+enum Primary {
+    #[oxifive(name = "RED")]
+    Red,
+    #[oxifive(name = "GREEN")]
+    Green,
+}
+
+#[test]
+fn read_enums() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/enums.h5")?;
+    let bool_dataset = file.dataset("bool")?;
+    let members = bool_dataset.datatype().enum_members()?.to_vec();
+    assert!(members[1].name == "TRUE" && members[1].value == [1]);
+    assert!(bool_dataset.read::<bool, Ix1>()? == array![true, false, true]);
+    assert!(bool_dataset.read::<i8, Ix1>()? == array![1, 0, 1]);
+    let color = file.dataset("color")?;
+    assert!(color.read::<u16, Ix1>()? == array![10, 0, 5, 5]);
+    assert!(
+        color.read_strings::<Ix1>()? == array!["BLUE", "RED", "GREEN", "GREEN"].mapv(String::from)
+    );
+    assert!(
+        color.read::<Color, Ix1>()? == array![Color::Blue, Color::Red, Color::Green, Color::Green]
+    );
+    assert!(color.read::<Primary, Ix1>().is_err());
+    assert!(color.read::<bool, Ix1>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_chunked_enums() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/fill_values.h5")?;
+    let colors = file.dataset("colors")?;
+    assert!(
+        colors.read::<Color, Ix1>()?
+            == array![
+                Color::Blue,
+                Color::Red,
+                Color::Green,
+                Color::Green,
+                Color::Red
+            ]
+    );
+    assert!(
+        colors.read_strings::<Ix1>()?
+            == array!["BLUE", "RED", "GREEN", "GREEN", "RED"].mapv(String::from)
+    );
+    // The missing chunk holds the fill value of the dataset
+    let filled = file.dataset("colors_filled")?.read::<Color, Ix1>()?;
+    assert!(
+        filled
+            == array![
+                Color::Blue,
+                Color::Red,
+                Color::Green,
+                Color::Green,
+                Color::Red
+            ]
+    );
+    // Without a fill value, the missing chunk holds zero, which is no member
+    assert!(file
+        .dataset("colors_unfilled")?
+        .read::<Color, Ix1>()
+        .is_err());
+    assert!(file.dataset("colors_unfilled")?.read::<u8, Ix1>()? == array![3, 1, 0, 0, 1]);
+    Ok(())
+}
+
+#[test]
+fn read_arrays() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/arrays.h5")?;
+    let matrices = file.dataset("matrices")?.read::<f64, Ix3>()?;
+    assert!(matrices.shape() == [2, 2, 3]);
+    assert!(matrices[[1, 0, 2]] == 8.0);
+    let vectors = file.dataset("vectors_v2")?;
+    assert!(
+        vectors.read::<i32, Ix3>()? == array![[[-6, -5, -4], [-3, -2, -1]], [[0, 1, 2], [3, 4, 5]]]
+    );
+    assert!(vectors.read::<[i32; 3], Ix2>()?[[1, 0]] == [0, 1, 2]);
+    assert!(vectors.read::<[i32; 2], Ix2>().is_err());
+    assert!(vectors.read::<String, Ix3>().is_err());
+    Ok(())
+}
+
+#[test]
+fn read_variable_length_sequences() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/vlen_sequences.h5")?;
+    let events = file.dataset("events")?.read_vlen::<i32, Ix1>()?;
+    assert!(events.to_vec() == vec![vec![1, 2, 3], vec![], vec![-4], vec![5, 6]]);
+    let grid = file.dataset("grid")?.read::<Vec<f64>, Ix2>()?;
+    assert!(grid[[0, 1]] == [1.5, 2.5] && grid[[1, 0]].is_empty());
+    assert!(grid[[1, 1]] == [3.5, 4.5, 5.5]);
+    let converted = file.dataset("events")?.read_vlen::<f64, Ix1>()?;
+    assert!(converted[2] == [-4.0]);
+    assert!(file.dataset("events")?.read_vlen::<u8, Ix1>().is_err());
+    assert!(file.dataset("events")?.read::<i32, Ix1>().is_err());
+    assert!(file.dataset("events")?.read_strings::<Ix1>().is_err());
+    // Heap object indices beyond 16 bits must not wrap around to another object
+    assert!(file
+        .dataset("wrapped_index")?
+        .read_vlen::<i32, Ix1>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn read_opaque() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/opaque_bitfield_time.h5")?;
+    let (data, tag) = file.dataset("opaque")?.read_opaque::<Ix1>()?;
+    assert!(tag == "sensor frame");
+    assert!(data.to_vec() == vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9, 10, 11]]);
+    assert!(file.dataset("opaque")?.read::<u32, Ix1>().is_err());
+    assert!(file.dataset("time32")?.read_opaque::<Ix1>().is_err());
+    assert!(file.dataset("opaque_empty").is_err());
+    Ok(())
+}
+
+#[test]
+fn read_bitfields_and_times() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/opaque_bitfield_time.h5")?;
+    assert!(file.dataset("bitfield8")?.read::<u8, Ix1>()? == array![0b1010, 0xFF]);
+    assert!(file.dataset("bitfield16_be")?.read::<u16, Ix1>()? == array![0x0102, 0x8000]);
+    assert!(file.dataset("bitfield_packed")?.read::<u16, Ix1>()? == array![0xAB, 0x01]);
+    assert!(file.dataset("bitfield8")?.read::<u16, Ix1>().is_err());
+    assert!(file.dataset("time32")?.read::<i32, Ix1>()? == array![0, 1_700_000_000]);
+    assert!(file.dataset("time64_be")?.read::<i64, Ix1>()? == array![86400, 1_700_000_000]);
+    assert!(file.dataset("time64_be")?.read::<f64, Ix1>().is_err());
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, H5Type)]
+struct Vector {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[test]
+fn read_committed_datatypes() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/committed.h5")?;
+    let vector = file.datatype("vector")?;
+    assert!(vector.compound_member("z")?.byte_offset == 8);
+    assert!(matches!(
+        file.object("vector")?,
+        oxifive::Object::Datatype(_)
+    ));
+    assert!(matches!(file.object("group")?, oxifive::Object::Group(_)));
+    assert!(file.group("vector").is_err());
+    for name in &["points", "points_v1"] {
+        let points = file.dataset(name)?;
+        assert!(points.datatype() == vector);
+        let expected = Vector {
+            x: 4.0,
+            y: 5.0,
+            z: 6.0,
+        };
+        assert!(points.read::<Vector, Ix1>()?[1] == expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn read_unwritten_contiguous_datasets() -> Result<(), oxifive::error::Error> {
+    // The file has a userblock, so undefined addresses must not be offset
+    let file = open("tests/files/fill_values.h5")?;
+    assert!(file.dataset("unwritten")?.read::<f64, Ix1>()? == array![0.0, 0.0, 0.0]);
+    assert!(file.dataset("unwritten_filled")?.read::<i32, Ix1>()? == array![7, 7]);
+    assert!(file
+        .dataset("unwritten_colors")?
+        .read::<Color, Ix1>()
+        .is_err());
+    Ok(())
+}
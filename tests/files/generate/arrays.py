@@ -0,0 +1,18 @@
+import struct, sys
+from h5w import *
+
+f = File()
+# Two 2x3 matrices of f64
+mat = dt_array([2, 3], dt_float(8))
+f.contiguous('matrices', mat, ds_simple([2]), struct.pack('<12d', *range(12)))
+# Version 2 array datatype of three i32 per element, 2-D dataspace
+vec = dt_array([3], dt_int(4), version=2)
+f.contiguous('vectors_v2', vec, ds_simple([2, 2]), struct.pack('<12i', *range(-6, 6)))
+# Compound v1 with an array member, and v3 with an array datatype member
+c1 = dt_compound([('id', 0, dt_int(4)), ('position', 4, dt_float(4), [3])], 16, version=1)
+f.contiguous('compound_v1', c1, ds_simple([2]),
+             struct.pack('<i3f', 1, 1.0, 2.0, 3.0) + struct.pack('<i3f', 2, 4.0, 5.0, 6.0))
+c3 = dt_compound([('id', 0, dt_int(4)), ('position', 4, dt_array([3], dt_float(4)))], 16)
+f.contiguous('compound_v3', c3, ds_simple([2]),
+             struct.pack('<i3f', 1, 1.0, 2.0, 3.0) + struct.pack('<i3f', 2, 4.0, 5.0, 6.0))
+f.save(sys.argv[1])
@@ -0,0 +1,10 @@
+import struct, sys
+from h5w import *
+
+f = File()
+f.contiguous('float32', dt_float(4, big=True), ds_simple([2, 3]), struct.pack('>6f', 1, 2, 3, 4, 5, 6))
+f.contiguous('float64', dt_float(8, big=True), ds_simple([3]), struct.pack('>3d', 0.5, -1.25, 1e300))
+chunks = grid_btree_chunks(f, list(range(16)), [4, 4], [2, 2], '>f')
+f.dataset('chunked', dt_float(4, big=True), ds_simple([4, 4]),
+          layout_chunked(chunk_btree(f, chunks, 2), [2, 2], 4))
+f.save(sys.argv[1])
@@ -0,0 +1,16 @@
+import struct, sys
+from h5w import *
+
+f = File()
+vector = dt_compound([('x', 0, dt_float(4)), ('y', 4, dt_float(4)), ('z', 8, dt_float(4))], 12)
+committed = f.object_header([(DATATYPE, vector, 1)])
+f.links.append(('vector', committed))
+data = struct.pack('<6f', 1, 2, 3, 4, 5, 6)
+shared_v3 = bytes([3, 2]) + u64(committed)
+shared_v1 = bytes([1, 0]) + b'\0' * 6 + u64(committed)
+for name, shared in [('points', shared_v3), ('points_v1', shared_v1)]:
+    addr = f.alloc(data)
+    msgs = [(DATASPACE, ds_simple([2])), (DATATYPE, shared, 0x03), (LAYOUT, layout_contiguous(addr, len(data)))]
+    f.links.append((name, f.object_header(msgs)))
+f.links.append(('group', f.object_header([])))
+f.save(sys.argv[1])
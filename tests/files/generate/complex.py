@@ -0,0 +1,11 @@
+import struct, sys
+from h5w import *
+
+f = File()
+c128 = dt_compound([('r', 0, dt_float(8)), ('i', 8, dt_float(8))], 16)
+f.contiguous('fft', c128, ds_simple([2, 2]), struct.pack('<8d', 1, 0, 0, 1, -1, 0.5, 2, -2))
+c64 = dt_compound([('real', 0, dt_float(4)), ('imag', 4, dt_float(4))], 8)
+f.contiguous('octave', c64, ds_simple([2]), struct.pack('<4f', 1.5, -1, 0, 3))
+other = dt_compound([('x', 0, dt_float(4)), ('y', 4, dt_float(4))], 8)
+f.contiguous('not_complex', other, ds_simple([1]), struct.pack('<2f', 1, 2))
+f.save(sys.argv[1])
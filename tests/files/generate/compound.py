@@ -0,0 +1,16 @@
+import struct, sys
+from h5w import *
+
+f = File()
+records = [(0.5, b'first', 1, 7, 1.0), (-1.5, b'second', 2, 7, -3.0), (1e10, b'', 3, 7, 2e10)]
+data = b''.join(struct.pack('<d6s2xiB3xd', *record) for record in records)
+for version in (1, 2, 3):
+    record = dt_compound([('value', 0, dt_float(8)), ('label name', 8, dt_string(6, 1)),
+                          ('id', 16, dt_int(4)), ('unused', 20, dt_int(1, signed=False)),
+                          ('weight', 24, dt_float(8))], 32, version=version)
+    f.contiguous('records_v%d' % version, record, ds_simple([3]), data)
+# Members of the nested compound are not in the order of their offsets
+point = dt_compound([('y', 4, dt_float(4)), ('x', 0, dt_float(4))], 8)
+nested = dt_compound([('point', 4, point), ('id', 0, dt_int(2)), ('pad', 2, dt_int(2, signed=False))], 12)
+f.contiguous('nested', nested, ds_simple([2]), struct.pack('<hH2f', 7, 0, 1, 2) + struct.pack('<hH2f', -8, 0, 3, 4))
+f.save(sys.argv[1])
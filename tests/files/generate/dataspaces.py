@@ -0,0 +1,13 @@
+import struct, sys
+from h5w import *
+
+f = File()
+f.contiguous('scalar', dt_float(8), ds_scalar(), struct.pack('<d', 2.5))
+f.contiguous('scalar_v1', dt_int(4), ds_simple([], version=1), struct.pack('<i', 42))
+f.dataset('empty', dt_float(4), ds_null(), layout_contiguous(UNDEF, 0))
+f.contiguous('vector', dt_int(4), ds_simple([2]), struct.pack('<2i', 1, 2))
+f.contiguous('appendable', dt_int(2), ds_simple([2, 3], maxshape=[None, 3]),
+             struct.pack('<6h', 1, 2, 3, 4, 5, 6))
+f.contiguous('bounded_v1', dt_int(4), ds_simple([2], maxshape=[10], version=1, permutation=[0]),
+             struct.pack('<2i', 7, 8))
+f.save(sys.argv[1])
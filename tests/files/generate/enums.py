@@ -0,0 +1,10 @@
+import struct, sys
+from h5w import *
+
+f = File()
+# The boolean enum that h5py writes
+boolean = dt_enum(dt_int(1), [('FALSE', 0), ('TRUE', 1)])
+f.contiguous('bool', boolean, ds_simple([3]), bytes([1, 0, 1]))
+color = dt_enum(dt_int(2, signed=False, big=True), [('RED', 0), ('GREEN', 5), ('BLUE', 10)])
+f.contiguous('color', color, ds_simple([4]), struct.pack('>4H', 10, 0, 5, 5))
+f.save(sys.argv[1])
@@ -0,0 +1,32 @@
+import struct, sys
+from h5w import *
+
+f = File()
+color = dt_enum(dt_int(1), [('RED', 1), ('GREEN', 2), ('BLUE', 3)])
+
+
+def chunked(name, values, chunk, written, fill=None):
+    """One-dimensional chunked dataset of one-byte values, with only the chunks
+    at the `written` indices allocated."""
+    chunks = []
+    for index in written:
+        data = bytes(values[index * chunk:(index + 1) * chunk]).ljust(chunk, b'\0')
+        chunks.append(((index * chunk,), f.alloc(data), chunk))
+    btree = chunk_btree(f, chunks, 1)
+    extra = [] if fill is None else [(FILLVALUE, fill_value_v3(fill))]
+    f.dataset(name, color, ds_simple([len(values)]), layout_chunked(btree, [chunk], 1), extra)
+
+
+# No member has the value zero, which is never needed as all chunks are written
+chunked('colors', [3, 1, 2, 2, 1], 2, [0, 1, 2])
+# The middle chunk is missing and holds the fill value
+chunked('colors_filled', [3, 1, 0, 0, 1], 2, [0, 2], fill=bytes([2]))
+# The middle chunk is missing and the default fill value of zero is no member
+chunked('colors_unfilled', [3, 1, 0, 0, 1], 2, [0, 2])
+
+# Contiguous datasets that were never written have no storage allocated
+f.dataset('unwritten', dt_float(8), ds_simple([3]), layout_contiguous(UNDEF, 24))
+f.dataset('unwritten_filled', dt_int(4), ds_simple([2]), layout_contiguous(UNDEF, 8),
+          [(FILLVALUE, fill_value_v3(struct.pack('<i', 7)))])
+f.dataset('unwritten_colors', color, ds_simple([2]), layout_contiguous(UNDEF, 2))
+f.save(sys.argv[1], userblock=512)
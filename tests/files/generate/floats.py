@@ -0,0 +1,25 @@
+import struct, sys
+from h5w import *
+
+f = File()
+halves = [1.0, -2.5, 65504.0, 2 ** -24]
+f.contiguous('f16', dt_float(2), ds_simple([4]), struct.pack('<4e', *halves))
+f.contiguous('f16_be', dt_float(2, big=True), ds_simple([4]), struct.pack('>4e', *halves))
+f.contiguous('f16_special', dt_float(2), ds_simple([2]), struct.pack('<2e', float('inf'), float('nan')))
+
+
+def f24(value):
+    """Sign bit, seven exponent bits with a bias of 63 and sixteen mantissa bits."""
+    sign = 1 if value < 0 else 0
+    mantissa, exponent = abs(value), 63
+    while mantissa >= 2:
+        mantissa, exponent = mantissa / 2, exponent + 1
+    while mantissa < 1:
+        mantissa, exponent = mantissa * 2, exponent - 1
+    bits = sign << 23 | exponent << 16 | int((mantissa - 1) * (1 << 16))
+    return bits.to_bytes(3, 'little')
+
+
+f.contiguous('f24', dt_float(3, exponent_size=7, mantissa_size=16), ds_simple([3]),
+             b''.join(f24(value) for value in (1.5, -0.25, 3.0)))
+f.save(sys.argv[1])
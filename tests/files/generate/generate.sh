@@ -0,0 +1,18 @@
+#!/bin/sh
+# Regenerates the test files in tests/files with the hand-written HDF5 encoder
+# in h5w.py, one script per file. simple.h5 and hierarchy.h5 were written by
+# libhdf5 and are not generated.
+set -e
+cd "$(dirname "$0")"
+for name in arrays big_endian committed complex compound dataspaces enums fill_values floats \
+    integers layouts opaque_bitfield_time references regions strings vlen_sequences vlen_strings; do
+    python3 "$name.py" "../$name.h5"
+done
+for version in 1 2 3; do
+    python3 superblock.py "../superblock_v$version.h5" "$version"
+done
+python3 offsets.py ../offsets_2_lengths_2.h5 2 2
+python3 offsets.py ../offsets_4_lengths_4.h5 4 4
+python3 offsets.py ../offsets_4_lengths_8.h5 4 8
+python3 userblock.py ../userblock_512.h5 512
+python3 userblock.py ../userblock_1024.h5 1024
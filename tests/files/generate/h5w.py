@@ -0,0 +1,462 @@
+"""Minimal HDF5 writer for generating oxifive test fixtures.
+
+`File` writes superblock version 2 or 3 files whose objects have version 2
+object headers (OHDR), and `LegacyFile` writes superblock version 0 or 1 files
+whose objects have version 1 object headers and whose groups are symbol tables.
+Messages are supplied as raw bytes built with the helpers below.
+"""
+import struct
+
+M32 = 0xFFFFFFFF
+
+
+def rot(x, k):
+    return ((x << k) | (x >> (32 - k))) & M32
+
+
+def lookup3(data):
+    length = len(data)
+    a = b = c = (0xDEADBEEF + length) & M32
+    i = 0
+    while length - i > 12:
+        a = (a + struct.unpack_from('<I', data, i)[0]) & M32
+        b = (b + struct.unpack_from('<I', data, i + 4)[0]) & M32
+        c = (c + struct.unpack_from('<I', data, i + 8)[0]) & M32
+        a = (a - c) & M32; a ^= rot(c, 4); c = (c + b) & M32
+        b = (b - a) & M32; b ^= rot(a, 6); a = (a + c) & M32
+        c = (c - b) & M32; c ^= rot(b, 8); b = (b + a) & M32
+        a = (a - c) & M32; a ^= rot(c, 16); c = (c + b) & M32
+        b = (b - a) & M32; b ^= rot(a, 19); a = (a + c) & M32
+        c = (c - b) & M32; c ^= rot(b, 4); b = (b + a) & M32
+        i += 12
+    if length - i == 0:
+        return c
+    tail = data[i:] + b'\0' * (12 - (length - i))
+    a = (a + struct.unpack_from('<I', tail, 0)[0]) & M32
+    b = (b + struct.unpack_from('<I', tail, 4)[0]) & M32
+    c = (c + struct.unpack_from('<I', tail, 8)[0]) & M32
+    c ^= b; c = (c - rot(b, 14)) & M32
+    a ^= c; a = (a - rot(c, 11)) & M32
+    b ^= a; b = (b - rot(a, 25)) & M32
+    c ^= b; c = (c - rot(b, 16)) & M32
+    a ^= c; a = (a - rot(c, 4)) & M32
+    b ^= a; b = (b - rot(a, 14)) & M32
+    c ^= b; c = (c - rot(b, 24)) & M32
+    return c
+
+
+UNDEF = 0xFFFFFFFFFFFFFFFF
+
+# Message types
+DATASPACE, DATATYPE, FILLVALUE, LINK, LAYOUT, FILTER, ATTRIBUTE = 1, 3, 5, 6, 8, 11, 12
+SYMBOL_TABLE, BTREE_K = 17, 19
+
+
+def u8(v): return struct.pack('<B', v)
+def u16(v): return struct.pack('<H', v)
+def u32(v): return struct.pack('<I', v)
+def u64(v): return struct.pack('<Q', v)
+
+
+def uint(v, size):
+    """Unsigned integer of `size` bytes, where UNDEF has all bits set."""
+    return ((1 << 8 * size) - 1 if v == UNDEF else v).to_bytes(size, 'little')
+
+
+# ---------------------------------------------------------------- datatypes
+
+def dt_header(cls, version, bits, size):
+    return bytes([(version << 4) | cls, bits & 0xFF, (bits >> 8) & 0xFF, (bits >> 16) & 0xFF]) + u32(size)
+
+
+def dt_int(size, signed=True, big=False, offset=0, precision=None):
+    bits = (0x08 if signed else 0) | (0x01 if big else 0)
+    return dt_header(0, 1, bits, size) + u16(offset) + u16(precision or size * 8)
+
+
+def dt_float(size, big=False, exponent_size=None, mantissa_size=None):
+    """IEEE floats of 2, 4 or 8 bytes, or custom formats with the given field sizes."""
+    default_exponent, default_mantissa = {2: (5, 10), 4: (8, 23), 8: (11, 52)}.get(size, (None, None))
+    exponent_size = exponent_size or default_exponent
+    mantissa_size = mantissa_size or default_mantissa
+    sign = size * 8 - 1
+    props = u16(0) + u16(size * 8) + bytes([mantissa_size, exponent_size, 0, mantissa_size])
+    props += u32((1 << (exponent_size - 1)) - 1)
+    bits = (0x20) | (0x01 if big else 0) | (sign << 8)
+    return dt_header(1, 1, bits, size) + props
+
+
+def dt_string(size, padding=0, cset=0):
+    return dt_header(3, 1, padding | (cset << 4), size)
+
+
+def dt_vlen_string(cset=0, padding=0):
+    return dt_header(9, 1, 1 | (padding << 4) | (cset << 8), 16) + dt_int(1, False)
+
+
+def dt_vlen(base):
+    return dt_header(9, 1, 0, 16) + base
+
+
+def dt_array(dims, base, version=3):
+    out = dt_header(10, version, 0, 0)
+    # size patched below
+    body = u8(len(dims))
+    if version < 3:
+        body += b'\0' * 3
+    for d in dims:
+        body += u32(d)
+    if version < 3:
+        for i in range(len(dims)):
+            body += u32(i)
+    size = base_size(base)
+    for d in dims:
+        size *= d
+    return dt_header(10, version, 0, size) + body + base
+
+
+def base_size(dt):
+    return struct.unpack_from('<I', dt, 4)[0]
+
+
+def dt_compound(members, size, version=3):
+    """members: list of (name, offset, datatype bytes)"""
+    body = b''
+    for member in members:
+        name, offset, dt = member[:3]
+        nb = name.encode() + b'\0'
+        if version < 3:
+            nb += b'\0' * ((8 - len(nb) % 8) % 8)
+        body += nb
+        if version == 1:
+            dims = member[3] if len(member) > 3 else []
+            body += u32(offset) + bytes([len(dims), 0, 0, 0]) + u32(0) + u32(0)
+            body += b''.join(u32(d) for d in dims + [0] * (4 - len(dims)))
+        elif version == 3:
+            osz = 1 if size < 256 else 2 if size < 65536 else 3 if size < 1 << 24 else 4
+            body += offset.to_bytes(osz, 'little')
+        else:
+            body += u32(offset)
+        body += dt
+    return dt_header(6, version, len(members), size) + body
+
+
+def dt_enum(base, members):
+    """members: list of (name, value), stored with version 3 unpadded names."""
+    body = base
+    for name, _ in members:
+        body += name.encode() + b'\0'
+    # Values are stored in the byte order of the base type
+    order = 'big' if base[1] & 0x01 else 'little'
+    for _, value in members:
+        body += value.to_bytes(base_size(base), order, signed=True)
+    return dt_header(8, 3, len(members), base_size(base)) + body
+
+
+def fill_value_v3(value=None):
+    if value is None:
+        return bytes([3, 0x02 | (2 << 2)])
+    return bytes([3, 0x02 | (2 << 2) | 0x20]) + u32(len(value)) + value
+
+
+def dt_reference(kind):
+    """kind 0: object (8 bytes), 1: dataset region (12 bytes)"""
+    return dt_header(7, 1, kind, 8 if kind == 0 else 12)
+
+
+def dt_opaque(size, tag):
+    tb = tag.encode() + b'\0'
+    tb += b'\0' * ((8 - len(tb) % 8) % 8)
+    return dt_header(5, 1, len(tb), size) + tb
+
+
+def dt_bitfield(size, big=False, offset=0, precision=None):
+    return dt_header(4, 1, 0x01 if big else 0, size) + u16(offset) + u16(precision or size * 8)
+
+
+def dt_time(size, big=False):
+    return dt_header(2, 1, 0x01 if big else 0, size) + u16(size * 8)
+
+
+# ---------------------------------------------------------------- dataspace
+
+def ds_simple(shape, maxshape=None, version=2, permutation=None, length_size=8):
+    flags = (1 if maxshape is not None else 0) | (2 if permutation is not None else 0)
+    if version == 2:
+        out = bytes([2, len(shape), flags, 1 if shape is not None else 2])
+    else:
+        out = bytes([1, len(shape), flags, 0]) + u32(0)
+    for d in shape:
+        out += uint(d, length_size)
+    if maxshape is not None:
+        for d in maxshape:
+            out += uint(UNDEF if d is None else d, length_size)
+    if permutation is not None:
+        for d in permutation:
+            out += uint(d, length_size)
+    return out
+
+
+def ds_scalar():
+    return bytes([2, 0, 0, 0])
+
+
+def ds_null():
+    return bytes([2, 0, 0, 2])
+
+
+# ---------------------------------------------------------------- file
+
+
+class File:
+    def __init__(self, version=2, offset_size=8, length_size=8):
+        self.version = version
+        self.offset_size = offset_size
+        self.length_size = length_size
+        self.buf = bytearray(self.superblock_size())
+        self.links = []
+
+    def superblock_size(self):
+        return 12 + 4 * self.offset_size + 4
+
+    def offset(self, v):
+        return uint(v, self.offset_size)
+
+    def length(self, v):
+        return uint(v, self.length_size)
+
+    def alloc(self, data, align=8):
+        while len(self.buf) % align:
+            self.buf += b'\0'
+        addr = len(self.buf)
+        self.buf += data
+        return addr
+
+    def reserve(self, size):
+        return self.alloc(b'\0' * size)
+
+    def patch(self, addr, data):
+        self.buf[addr:addr + len(data)] = data
+
+    def object_header(self, messages):
+        """messages: list of (type, bytes[, flags])"""
+        body = b''
+        for m in messages:
+            mtype, data = m[0], m[1]
+            flags = m[2] if len(m) > 2 else 0
+            body += bytes([mtype]) + u16(len(data)) + bytes([flags]) + data
+        head = b'OHDR' + bytes([2, 0x02]) + u32(len(body))
+        oh = head + body
+        return self.alloc(oh + u32(lookup3(oh)))
+
+    def dataset(self, name, dtype, space, layout, extra=(), link=True):
+        msgs = [(DATASPACE, space), (DATATYPE, dtype, 1)] + list(extra) + [(LAYOUT, layout)]
+        addr = self.object_header(msgs)
+        if link:
+            self.links.append((name, addr))
+        return addr
+
+    def contiguous(self, name, dtype, space, data, extra=(), link=True):
+        addr = self.alloc(data)
+        layout = layout_contiguous(addr, len(data), self.offset_size, self.length_size)
+        return self.dataset(name, dtype, space, layout, extra, link)
+
+    def group_header(self, links):
+        """Object header of a group holding the (name, address) links."""
+        messages = []
+        for name, addr in links:
+            nb = name.encode()
+            messages.append((LINK, bytes([1, 0, len(nb)]) + nb + self.offset(addr)))
+        return self.object_header(messages)
+
+    def group(self, name, links):
+        addr = self.group_header(links)
+        self.links.append((name, addr))
+        return addr
+
+    def global_heap(self, objects):
+        """objects: list of bytes, index 1..n. Returns collection address."""
+        body = b''
+        for i, obj in enumerate(objects, 1):
+            body += u16(i) + u16(1) + u32(0) + u64(len(obj)) + obj
+            body += b'\0' * ((8 - len(obj) % 8) % 8)
+        size = 16 + len(body)
+        free = max(4096 - size, 16)
+        size_total = size + free
+        body += u16(0) + u16(0) + u32(0) + u64(free) + b'\0' * (free - 16)
+        return self.alloc(b'GCOL' + bytes([1, 0, 0, 0]) + u64(size_total) + body)
+
+    def save(self, path, userblock=0, header=b'', extension=None):
+        """Writes the file after a userblock of `userblock` bytes that starts
+        with `header`. Version 3 superblocks can have an extension object with
+        the `extension` messages."""
+        root = self.group_header(self.links)
+        extension_addr = UNDEF if extension is None else self.object_header(extension)
+        end = len(self.buf)
+        # Addresses are relative to the superblock, which the userblock precedes
+        sb = b'\x89HDF\r\n\x1a\n' + bytes([self.version, self.offset_size, self.length_size, 0])
+        sb += self.offset(userblock) + self.offset(extension_addr) + self.offset(end) + self.offset(root)
+        self.patch(0, sb + u32(lookup3(sb)))
+        with open(path, 'wb') as f:
+            f.write(header.ljust(userblock, b'\0') + bytes(self.buf))
+
+
+class LegacyFile(File):
+    """Superblock version 0 or 1 file, where version 1 stores the K of chunk
+    B-trees in the superblock."""
+
+    def __init__(self, version=0, offset_size=8, length_size=8, chunk_btree_k=32):
+        self.chunk_btree_k = chunk_btree_k
+        super().__init__(version, offset_size, length_size)
+
+    def superblock_size(self):
+        fixed = 24 + (4 if self.version == 1 else 0)
+        return fixed + 4 * self.offset_size + self.symbol_table_entry_size()
+
+    def symbol_table_entry_size(self):
+        return self.length_size + self.offset_size + 24
+
+    def symbol_table_entry(self, name_offset, addr, scratch=b''):
+        cache_type = 1 if scratch else 0
+        return self.length(name_offset) + self.offset(addr) + u32(cache_type) + u32(0) + scratch.ljust(16, b'\0')
+
+    def object_header(self, messages):
+        """Version 1 object header, whose messages are aligned to eight bytes."""
+        body = b''
+        for m in messages:
+            mtype, data = m[0], m[1]
+            flags = m[2] if len(m) > 2 else 0
+            data += b'\0' * ((8 - len(data) % 8) % 8)
+            body += u16(mtype) + u16(len(data)) + bytes([flags, 0, 0, 0]) + data
+        head = bytes([1, 0]) + u16(len(messages)) + u32(1) + u32(len(body)) + u32(0)
+        return self.alloc(head + body)
+
+    def symbol_table(self, links):
+        """Writes the local heap and B-tree of a group holding the (name,
+        address) links, and returns their addresses."""
+        names = b'\0' * 8
+        entries = b''
+        for name, addr in sorted(links):
+            entries += self.symbol_table_entry(len(names), addr)
+            nb = name.encode() + b'\0'
+            names += nb + b'\0' * ((8 - len(nb) % 8) % 8)
+        data = self.alloc(names)
+        heap = self.alloc(b'HEAP' + bytes([0, 0, 0, 0]) + self.length(len(names)) + self.length(UNDEF) +
+                          self.offset(data))
+        node = self.alloc(b'SNOD' + bytes([1, 0]) + u16(len(links)) + entries)
+        btree = self.alloc(b'TREE' + bytes([0, 0]) + u16(1) + self.offset(UNDEF) + self.offset(UNDEF) +
+                           self.length(0) + self.offset(node) + self.length(len(names) - 8))
+        return btree, heap
+
+    def group_header(self, links):
+        btree, heap = self.symbol_table(links)
+        return self.object_header([(SYMBOL_TABLE, self.offset(btree) + self.offset(heap))])
+
+    def save(self, path, userblock=0, header=b''):
+        btree, heap = self.symbol_table(self.links)
+        root = self.object_header([(SYMBOL_TABLE, self.offset(btree) + self.offset(heap))])
+        end = len(self.buf)
+        sb = b'\x89HDF\r\n\x1a\n' + bytes([self.version, 0, 0, 0, 0, self.offset_size, self.length_size, 0])
+        sb += u16(4) + u16(16) + u32(0)
+        if self.version == 1:
+            sb += u16(self.chunk_btree_k) + u16(0)
+        sb += self.offset(userblock) + self.offset(UNDEF) + self.offset(end) + self.offset(UNDEF)
+        sb += self.symbol_table_entry(0, root, self.offset(btree) + self.offset(heap))
+        self.patch(0, sb)
+        with open(path, 'wb') as f:
+            f.write(header.ljust(userblock, b'\0') + bytes(self.buf))
+
+
+def layout_contiguous(addr, size, offset_size=8, length_size=8):
+    return bytes([3, 1]) + uint(addr, offset_size) + uint(size, length_size)
+
+
+def layout_compact(data):
+    return bytes([3, 0]) + u16(len(data)) + data
+
+
+def layout_chunked(btree_addr, chunk_shape, item_size, offset_size=8):
+    out = bytes([3, 2, len(chunk_shape) + 1]) + uint(btree_addr, offset_size)
+    for d in chunk_shape:
+        out += u32(d)
+    return out + u32(item_size)
+
+
+def chunk_btree(f, chunks, ndims):
+    """chunks: list of (offsets tuple, address, size). Single leaf node."""
+    node = b'TREE' + bytes([1, 0]) + u16(len(chunks)) + f.offset(UNDEF) + f.offset(UNDEF)
+    for offsets, addr, size in chunks:
+        node += u32(size) + u32(0)
+        for o in offsets:
+            node += u64(o)
+        node += u64(0)
+        node += f.offset(addr)
+    # final key
+    node += u32(0) + u32(0) + u64(0) * (ndims + 1)
+    return f.alloc(node)
+
+
+def grid_btree_chunks(f, values, shape, chunk, fmt):
+    """Writes a row-major 2D list of values as chunks padded with zeros, and
+    returns them as the (offsets, address, size) entries of chunk_btree."""
+    chunks = []
+    for r0 in range(0, shape[0], chunk[0]):
+        for c0 in range(0, shape[1], chunk[1]):
+            items = [values[r * shape[1] + c] if r < shape[0] and c < shape[1] else 0
+                     for r in range(r0, r0 + chunk[0]) for c in range(c0, c0 + chunk[1])]
+            data = struct.pack(fmt[0] + '%d' % len(items) + fmt[1:], *items)
+            chunks.append(((r0, c0), f.alloc(data), len(data)))
+    return chunks
+
+
+def layout_legacy(layout_class, dims, addr=None, data=None, version=1):
+    out = bytes([version, len(dims), layout_class]) + b'\0' * 5
+    if layout_class != 0:
+        out += u64(addr)
+    for d in dims:
+        out += u32(d)
+    if layout_class == 0:
+        out += u32(len(data)) + data
+    return out
+
+
+def layout_v4_chunked(chunk_shape, item_size, index_type, index_info, addr, flags=0, encoded_length=4):
+    dims = list(chunk_shape) + [item_size]
+    out = bytes([4, 2, flags, len(dims), encoded_length])
+    for d in dims:
+        out += (d % (1 << 8 * encoded_length)).to_bytes(encoded_length, 'little')
+    return out + bytes([index_type]) + index_info + u64(addr)
+
+
+def filter_deflate(level=6):
+    name = b'deflate\0'
+    return bytes([1, 1]) + b'\0' * 6 + u16(1) + u16(len(name)) + u16(0) + u16(1) + name + u32(level) + u32(0)
+
+
+def fixed_array(f, entries, page_bits, entry_size=8, client=0):
+    """entries: list of encoded entries. Pages whose entries are all None are left uninitialized."""
+    page_entries = 1 << page_bits
+    n = len(entries)
+    undef_entry = lambda: u64(UNDEF) + b'\0' * (entry_size - 8)
+    header = b'FAHD' + bytes([0, client, entry_size, page_bits]) + u64(n)
+    header_addr = f.alloc(header + u64(0) + u32(0))
+    if n > page_entries:
+        pages = [entries[i:i + page_entries] for i in range(0, n, page_entries)]
+        bitmap = bytearray((len(pages) + 7) // 8)
+        for i, page in enumerate(pages):
+            if any(e is not None for e in page):
+                bitmap[i // 8] |= 0x80 >> (i % 8)
+        prefix = b'FADB' + bytes([0, client]) + u64(header_addr) + bytes(bitmap)
+        block = prefix + u32(lookup3(prefix))
+        for i, page in enumerate(pages):
+            body = b''.join(e if e is not None else undef_entry() for e in page)
+            body += u32(lookup3(body))
+            block += body
+        dblock_addr = f.alloc(block)
+    else:
+        prefix = b'FADB' + bytes([0, client]) + u64(header_addr)
+        prefix += b''.join(e if e is not None else undef_entry() for e in entries)
+        dblock_addr = f.alloc(prefix + u32(lookup3(prefix)))
+    header += u64(dblock_addr)
+    f.patch(header_addr, header + u32(lookup3(header)))
+    return header_addr
@@ -0,0 +1,15 @@
+import struct, sys
+from h5w import *
+
+f = File()
+for size, code in [(1, 'b'), (2, 'h'), (4, 'i'), (8, 'q')]:
+    f.contiguous('i%d' % (size * 8), dt_int(size), ds_simple([3]), struct.pack('<3' + code, -5, 0, 7))
+    maximum = (1 << size * 8) - 1
+    f.contiguous('u%d' % (size * 8), dt_int(size, signed=False), ds_simple([3]),
+                 struct.pack('<3' + code.upper(), 5, 0, maximum))
+# Twelve significant bits, starting at bit 4 of each element
+packed = [(value & 0xFFF) << 4 for value in (-3, 100, 2047)]
+f.contiguous('packed12', dt_int(2, offset=4, precision=12), ds_simple([3]), struct.pack('<3H', *packed))
+# Booleans are one-byte integers, where any value other than zero is true
+f.contiguous('bool', dt_int(1, signed=False), ds_simple([4]), bytes([0, 1, 2, 0]))
+f.save(sys.argv[1])
@@ -0,0 +1,101 @@
+import struct, sys, zlib
+from h5w import *
+
+f = File()
+f.dataset('compact', dt_float(8), ds_simple([2, 2]),
+          layout_compact(struct.pack('<4d', 1.0, 2.0, 3.0, 4.0)))
+f.dataset('compact_scalar', dt_int(4), ds_scalar(), layout_compact(struct.pack('<i', -5)))
+f.dataset('compact_strings', dt_string(3), ds_simple([2]), layout_compact(b'abcxy\0'))
+values = struct.pack('<6i', 1, 2, 3, 4, 5, 6)
+for version in (1, 2):
+    f.dataset('contiguous_v%d' % version, dt_int(4), ds_simple([2, 3]),
+              layout_legacy(1, [2, 3, 4], addr=f.alloc(values), version=version))
+    f.dataset('compact_v%d' % version, dt_int(4), ds_simple([2, 3]),
+              layout_legacy(0, [2, 3, 4], data=values, version=version))
+    # Two chunks of shape [1, 3], written as separate allocations
+    chunks = [((row, 0), f.alloc(values[row * 12:(row + 1) * 12]), 12) for row in (0, 1)]
+    btree = chunk_btree(f, chunks, 2)
+    f.dataset('chunked_v%d' % version, dt_int(4), ds_simple([2, 3]),
+              layout_legacy(2, [1, 3, 4], addr=btree, version=version))
+
+# Layout version 4 chunk indexes
+def grid_chunks(values, shape, chunk, fmt):
+    """Splits a row-major 2D list of values into padded chunks in row-major order."""
+    chunks = []
+    for r0 in range(0, shape[0], chunk[0]):
+        for c0 in range(0, shape[1], chunk[1]):
+            items = []
+            for r in range(r0, r0 + chunk[0]):
+                for c in range(c0, c0 + chunk[1]):
+                    inside = r < shape[0] and c < shape[1]
+                    items.append(values[r * shape[1] + c] if inside else 0)
+            chunks.append(struct.pack('<%d%s' % (len(items), fmt), *items))
+    return chunks
+
+
+f.dataset('single_chunk', dt_int(4), ds_simple([2, 3]),
+          layout_v4_chunked([2, 3], 4, 1, b'', f.alloc(values)))
+compressed = zlib.compress(values)
+f.dataset('single_chunk_deflate', dt_int(4), ds_simple([2, 3]),
+          layout_v4_chunked([2, 3], 4, 1, u64(len(compressed)) + u32(0), f.alloc(compressed), flags=2),
+          extra=[(FILTER, filter_deflate())])
+
+grid = list(range(15))
+chunks = grid_chunks(grid, [5, 3], [2, 2], 'i')
+f.dataset('implicit', dt_int(4), ds_simple([5, 3]),
+          layout_v4_chunked([2, 2], 4, 2, b'', f.alloc(b''.join(chunks))))
+
+chunks = grid_chunks(grid, [5, 3], [2, 2], 'h')
+# The chunk of rows 2 and 3, columns 0 and 1 was never written
+entries = [u64(f.alloc(chunk)) for chunk in chunks]
+entries[2] = None
+f.dataset('fixed_array', dt_int(2), ds_simple([5, 3]),
+          layout_v4_chunked([2, 2], 2, 3, bytes([10]), fixed_array(f, entries, 10)))
+
+entries = [u64(f.alloc(struct.pack('<i', 10 * i))) for i in range(10)]
+entries[4:8] = [None] * 4
+f.dataset('fixed_array_paged', dt_int(4), ds_simple([10]),
+          layout_v4_chunked([1], 4, 3, bytes([2]), fixed_array(f, entries, 2)))
+
+# The partial edge chunk is stored unfiltered
+first = zlib.compress(struct.pack('<2d', 0.5, 1.5))
+entries = [u64(f.alloc(first)) + u32(len(first)) + u32(0),
+           u64(f.alloc(struct.pack('<2d', 2.5, 0.0)))+ u32(16) + u32(0)]
+f.dataset('fixed_array_deflate', dt_float(8), ds_simple([3]),
+          layout_v4_chunked([2], 8, 3, bytes([10]), fixed_array(f, entries, 10, entry_size=16, client=1),
+                            flags=1),
+          extra=[(FILTER, filter_deflate())])
+
+# Chunks of datasets that can be resized are numbered along the maximum shape,
+# and chunks left outside the current shape after shrinking are ignored
+resizable = list(range(16))
+chunks = grid_chunks(resizable, [4, 4], [2, 2], 'i')
+outside = struct.pack('<4i', 99, 99, 99, 99)
+implicit_chunks = [outside] * 25
+fixed_entries = [None] * 25
+for index, chunk in enumerate(chunks):
+    implicit_chunks[(index // 2) * 5 + index % 2] = chunk
+    fixed_entries[(index // 2) * 5 + index % 2] = u64(f.alloc(chunk))
+fixed_entries[2] = fixed_entries[24] = u64(f.alloc(outside))
+f.dataset('implicit_resizable', dt_int(4), ds_simple([4, 4], maxshape=[10, 10]),
+          layout_v4_chunked([2, 2], 4, 2, b'', f.alloc(b''.join(implicit_chunks))))
+f.dataset('fixed_array_resizable', dt_int(4), ds_simple([4, 4], maxshape=[10, 10]),
+          layout_v4_chunked([2, 2], 4, 3, bytes([10]), fixed_array(f, fixed_entries, 10)))
+
+# Filters are only valid for chunked storage
+f.dataset('compact_filtered', dt_int(4), ds_simple([2]), layout_compact(struct.pack('<2i', 1, 2)),
+          extra=[(FILTER, filter_deflate())])
+f.contiguous('contiguous_filtered', dt_int(4), ds_simple([2]), struct.pack('<2i', 1, 2),
+             extra=[(FILTER, filter_deflate())])
+
+# Indexes for datasets with unlimited dimensions, which cannot be read yet
+f.dataset('extensible_array', dt_int(4), ds_simple([3], maxshape=[None]),
+          layout_v4_chunked([2], 4, 4, bytes([32, 4, 4, 16, 10]), f.alloc(b'EAHD' + bytes(60))))
+f.dataset('btree_v2', dt_int(4), ds_simple([2, 3], maxshape=[None, None]),
+          layout_v4_chunked([1, 3], 4, 5, u32(2048) + bytes([100, 40]), f.alloc(b'BTHD' + bytes(60))))
+
+# Chunk dimensions are encoded with one to eight bytes
+for length in [0, 9]:
+    f.dataset('encoded_length_%d' % length, dt_int(4), ds_simple([2]),
+              layout_v4_chunked([2], 4, 1, b'', UNDEF, encoded_length=length))
+f.save(sys.argv[1])
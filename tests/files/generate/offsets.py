@@ -0,0 +1,18 @@
+import struct, sys
+from h5w import *
+
+# Offsets and lengths of the given sizes, in a version 2 superblock for 2-byte
+# offsets and a version 0 or 1 superblock otherwise
+offset_size, length_size = int(sys.argv[2]), int(sys.argv[3])
+if offset_size == 2:
+    f = File(offset_size=offset_size, length_size=length_size)
+else:
+    f = LegacyFile(version=0 if length_size == 4 else 1, offset_size=offset_size,
+                   length_size=length_size)
+chunks = grid_btree_chunks(f, list(range(16)), [4, 4], [2, 2], '<f')
+chunked = f.dataset('chunked', dt_float(4), ds_simple([4, 4], length_size=length_size),
+                    layout_chunked(chunk_btree(f, chunks, 2), [2, 2], 4, offset_size), link=False)
+f.group('group', [('chunked', chunked)])
+f.contiguous('contiguous', dt_float(4), ds_simple([2, 3], length_size=length_size),
+             struct.pack('<6f', 1, 2, 3, 4, 5, 6))
+f.save(sys.argv[1])
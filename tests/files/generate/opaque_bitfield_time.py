@@ -0,0 +1,14 @@
+import struct, sys
+from h5w import *
+
+f = File()
+f.contiguous('opaque', dt_opaque(4, 'sensor frame'), ds_simple([3]), bytes(range(12)))
+f.contiguous('bitfield8', dt_bitfield(1), ds_simple([2]), bytes([0b1010, 0xFF]))
+f.contiguous('bitfield16_be', dt_bitfield(2, big=True), ds_simple([2]), struct.pack('>2H', 0x0102, 0x8000))
+f.contiguous('bitfield_packed', dt_bitfield(2, offset=4, precision=8), ds_simple([2]),
+             struct.pack('<2H', (0xAB << 4) | 0xF, (0x01 << 4) | 0xF000))
+f.contiguous('time32', dt_time(4), ds_simple([2]), struct.pack('<2I', 0, 1700000000))
+f.contiguous('time64_be', dt_time(8, big=True), ds_simple([2]), struct.pack('>2q', 86400, 1700000000))
+# Elements of a datatype without any bytes cannot be told apart
+f.contiguous('opaque_empty', dt_opaque(0, 'empty'), ds_simple([3]), b'')
+f.save(sys.argv[1])
@@ -0,0 +1,10 @@
+import struct, sys
+from h5w import *
+
+f = File()
+data = f.contiguous('data', dt_float(8), ds_simple([3]), struct.pack('<3d', 0.5, 1.5, 2.5))
+group = f.object_header([])
+f.links.append(('group', group))
+refs = u64(data) + u64(group) + u64(UNDEF)
+f.contiguous('object_refs', dt_reference(0), ds_simple([3]), refs)
+f.save(sys.argv[1])
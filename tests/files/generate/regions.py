@@ -0,0 +1,94 @@
+import struct, sys, zlib
+from h5w import *
+
+f = File()
+series = f.contiguous('series', dt_float(8), ds_simple([10]), struct.pack('<10d', *[x * 0.5 for x in range(10)]))
+grid = f.contiguous('grid', dt_int(4), ds_simple([3, 4]), struct.pack('<12i', *range(12)))
+# Chunks of four deflated doubles: the middle chunk is corrupt and the last one
+# was never written, so only selections outside the middle chunk can be read
+first = zlib.compress(struct.pack('<4d', 0.5, 1.5, 2.5, 3.5))
+corrupt = b'not deflated'
+sparse = f.dataset('sparse_series', dt_float(8), ds_simple([10]),
+                   layout_chunked(chunk_btree(f, [((0,), f.alloc(first), len(first)),
+                                                  ((4,), f.alloc(corrupt), len(corrupt))], 1),
+                                  [4], 8),
+                   extra=[(FILTER, filter_deflate())])
+
+def hyperslab_v1(rank, blocks):
+    body = u32(rank) + u32(len(blocks))
+    for start, end in blocks:
+        body += b''.join(u32(x) for x in start + end)
+    return u32(2) + u32(1) + u32(0) + u32(len(body)) + body
+
+def hyperslab_v2_regular(start, stride, count, block):
+    body = u32(len(start))
+    for d in range(len(start)):
+        body += u64(start[d]) + u64(stride[d]) + u64(count[d]) + u64(block[d])
+    return u32(2) + u32(2) + u8(1) + u32(len(body)) + body
+
+def hyperslab_v3_irregular(rank, blocks, size):
+    enc = lambda v: v.to_bytes(size, 'little')
+    body = u32(rank) + enc(len(blocks))
+    for start, end in blocks:
+        body += b''.join(enc(x) for x in start + end)
+    return u32(2) + u32(3) + u8(0) + u8(size) + body
+
+def hyperslab_v3_regular(start, stride, count, block, size):
+    enc = lambda v: v.to_bytes(size, 'little')
+    body = u32(len(start))
+    for d in range(len(start)):
+        body += enc(start[d]) + enc(stride[d]) + enc(count[d]) + enc(block[d])
+    return u32(2) + u32(3) + u8(1) + u8(size) + body
+
+def points_v1(rank, points):
+    body = u32(rank) + u32(len(points))
+    for p in points:
+        body += b''.join(u32(x) for x in p)
+    return u32(1) + u32(1) + u32(0) + u32(len(body)) + body
+
+def points_v2(rank, points, size):
+    enc = lambda v: v.to_bytes(size, 'little')
+    body = u32(rank) + enc(len(points))
+    for p in points:
+        body += b''.join(enc(x) for x in p)
+    return u32(1) + u32(2) + u8(size) + body
+
+def select_all():
+    return u32(3) + u32(1) + u32(0) + u32(0)
+
+objects = [
+    u64(series) + hyperslab_v1(1, [([7], [8]), ([2], [4]), ([3], [3])]),
+    u64(grid) + points_v1(2, [[2, 1], [0, 3]]),
+    u64(grid) + hyperslab_v2_regular([0, 1], [2, 2], [2, 2], [1, 1]),
+    u64(series) + select_all(),
+    u64(grid) + hyperslab_v3_irregular(2, [([1, 0], [1, 1])], 4),
+    u64(series) + points_v2(1, [[9], [0]], 2),
+    u64(series) + hyperslab_v1(1, [([8], [11])]),
+    u64(sparse) + hyperslab_v1(1, [([1], [2]), ([9], [9])]),
+    u64(sparse) + points_v2(1, [[8], [0]], 2),
+    u64(sparse) + select_all(),
+]
+heap = f.global_heap(objects)
+refs = b''.join(u64(heap) + u32(i) for i in range(1, len(objects) + 1))
+f.contiguous('region_refs', dt_reference(1), ds_simple([len(objects)]), refs)
+# The same references in chunks of three, so the chunks never hold zeros
+chunks = [((start,), f.alloc(refs[start * 12:(start + 3) * 12].ljust(36, b'\0')), 36)
+          for start in range(0, len(objects), 3)]
+f.dataset('chunked_region_refs', dt_reference(1), ds_simple([len(objects)]),
+          layout_chunked(chunk_btree(f, chunks, 1), [3], 12))
+# Selections that cannot be read: unlimited counts and blocks, which are all
+# ones in the encoded size of the values, values that are not stored in one to
+# eight bytes, empty blocks and blocks beyond the range of coordinates
+invalid = [
+    u64(series) + hyperslab_v3_regular([0], [2], [0xFFFFFFFF], [1], 4),
+    u64(series) + hyperslab_v3_regular([0], [1], [1], [0xFFFF], 2),
+    u64(series) + u32(1) + u32(2) + u8(0) + u32(1) + u32(1) + u32(1),
+    u64(series) + hyperslab_v3_irregular(1, [([1], [1])], 9),
+    u64(series) + hyperslab_v2_regular([0], [1], [1], [0]),
+    u64(series) + hyperslab_v2_regular([UNDEF - 1], [1], [1], [4]),
+    u64(series) + hyperslab_v2_regular([1], [UNDEF - 1], [3], [1]),
+]
+heap = f.global_heap(invalid)
+f.contiguous('invalid_region_refs', dt_reference(1), ds_simple([len(invalid)]),
+             b''.join(u64(heap) + u32(i) for i in range(1, len(invalid) + 1)))
+f.save(sys.argv[1])
@@ -0,0 +1,21 @@
+import sys
+from h5w import *
+
+NULL_TERMINATED, NULL_PADDED, SPACE_PADDED = 0, 1, 2
+ASCII, UTF8 = 0, 1
+
+
+def strings(values, size, pad=b'\0'):
+    return b''.join(value.encode().ljust(size, pad) for value in values)
+
+
+f = File()
+f.contiguous('null_terminated', dt_string(6, NULL_TERMINATED), ds_simple([3]),
+             strings(['x', 'hello', 'abcdef'], 6))
+f.contiguous('null_padded', dt_string(4, NULL_PADDED), ds_simple([2, 2]),
+             strings(['a', 'bc', 'def', 'ghij'], 4))
+f.contiguous('space_padded', dt_string(5, SPACE_PADDED), ds_simple([2]), strings(['ab', '  cd'], 5, b' '))
+f.contiguous('utf8', dt_string(8, NULL_PADDED, UTF8), ds_simple([2]), strings(['été', '日本'], 8))
+# UTF-8 bytes in a string whose character set is ASCII
+f.contiguous('not_ascii', dt_string(4, NULL_PADDED), ds_simple([1]), strings(['é'], 4))
+f.save(sys.argv[1])
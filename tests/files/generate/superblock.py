@@ -0,0 +1,16 @@
+import struct, sys
+from h5w import *
+
+version = int(sys.argv[2])
+if version == 1:
+    # Version 1 superblocks store the K of chunk B-trees
+    f = LegacyFile(version=1, chunk_btree_k=2)
+    chunks = grid_btree_chunks(f, list(range(16)), [4, 4], [2, 2], '<f')
+    f.dataset('chunked', dt_float(4), ds_simple([4, 4]), layout_chunked(chunk_btree(f, chunks, 2), [2, 2], 4))
+    f.save(sys.argv[1])
+else:
+    # Version 3 superblocks can have an extension that sets the K of chunk B-trees
+    f = File(version=version)
+    f.contiguous('data', dt_float(4), ds_simple([2, 3]), struct.pack('<6f', 1, 2, 3, 4, 5, 6))
+    extension = [(BTREE_K, bytes([0]) + u16(64) + u16(16) + u16(4))] if version == 3 else None
+    f.save(sys.argv[1], extension=extension)
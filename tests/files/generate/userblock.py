@@ -0,0 +1,10 @@
+import struct, sys
+from h5w import *
+
+# MATLAB 7.3 files are HDF5 files after a userblock of at least 512 bytes
+size = int(sys.argv[2])
+f = LegacyFile() if size == 512 else File()
+f.contiguous('data', dt_float(4), ds_simple([2, 3]), struct.pack('<6f', 1, 2, 3, 4, 5, 6))
+text = b'MATLAB 7.3 MAT-file, Platform: GLNXA64, Created on: Mon Jan  1 00:00:00 2024 HDF5 schema 1.00 .'
+header = text.ljust(116, b' ') + b'\0' * 8 + u16(0x0200) + b'IM'
+f.save(sys.argv[1], userblock=size, header=header)
@@ -0,0 +1,28 @@
+import struct, sys
+from h5w import *
+
+f = File()
+rows = [[1, 2, 3], [], [-4], [5, 6]]
+heap = f.global_heap([struct.pack('<%di' % len(r), *r) for r in rows if r])
+data, index = b'', 1
+for r in rows:
+    if r:
+        data += u32(len(r)) + u64(heap) + u32(index)
+        index += 1
+    else:
+        data += u32(0) + u64(0) + u32(0)
+f.contiguous('events', dt_vlen(dt_int(4)), ds_simple([4]), data)
+grid = [[0.5], [1.5, 2.5], [], [3.5, 4.5, 5.5]]
+heap = f.global_heap([struct.pack('<%dd' % len(r), *r) for r in grid if r])
+data, index = b'', 1
+for r in grid:
+    if r:
+        data += u32(len(r)) + u64(heap) + u32(index)
+        index += 1
+    else:
+        data += u32(0) + u64(0) + u32(0)
+f.contiguous('grid', dt_vlen(dt_float(8)), ds_simple([2, 2]), data)
+# The 32-bit index of the only element truncates to the first object of the heap
+heap = f.global_heap([struct.pack('<i', 7)])
+f.contiguous('wrapped_index', dt_vlen(dt_int(4)), ds_simple([1]), u32(1) + u64(heap) + u32(0x10001))
+f.save(sys.argv[1])
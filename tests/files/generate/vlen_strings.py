@@ -0,0 +1,24 @@
+import sys
+from h5w import *
+
+ASCII, UTF8 = 0, 1
+f = File()
+long = 'longer string spanning more than eight bytes'
+heap = f.global_heap([s.encode() for s in ['a', 'héllo wörld', long, 'zero', 'one', 'two', 'three']])
+
+
+def element(index, length):
+    """Length of the string in bytes and its global heap ID."""
+    return u32(length) + u64(heap) + u32(index)
+
+
+# Empty strings have no heap object, and equal strings can share one
+empty = u32(0) + u64(UNDEF) + u32(0)
+f.contiguous('contiguous', dt_vlen_string(UTF8), ds_simple([5]),
+             element(1, 1) + empty + element(2, 13) + element(3, len(long)) + element(1, 1))
+# The last chunk is never written
+chunks = [((0,), f.alloc(element(4, 4) + element(5, 3)), 32),
+          ((2,), f.alloc(element(6, 3) + element(7, 5)), 32)]
+f.dataset('chunked', dt_vlen_string(ASCII), ds_simple([5]),
+          layout_chunked(chunk_btree(f, chunks, 1), [2], 16))
+f.save(sys.argv[1])
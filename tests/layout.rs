@@ -0,0 +1,110 @@
+use ndarray::{array, Ix1, Ix2};
+use oxifive::read::reference::Selection;
+
+mod common;
+use common::open;
+
+#[test]
+fn read_compact_layout() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    let compact = file.dataset("compact")?.read::<f64, Ix2>()?;
+    assert!(compact == array![[1.0, 2.0], [3.0, 4.0]]);
+    assert!(file.dataset("compact_scalar")?.read_scalar::<i32>()? == -5);
+    let strings = file.dataset("compact_strings")?.read_strings::<Ix1>()?;
+    assert!(strings == array!["abc".to_string(), "xy".to_string()]);
+    assert!(file
+        .dataset("compact_filtered")?
+        .read::<i32, Ix1>()
+        .is_err());
+    assert!(file
+        .dataset("contiguous_filtered")?
+        .read::<i32, Ix1>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn read_legacy_layout_versions() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    for name in &[
+        "contiguous_v1",
+        "contiguous_v2",
+        "compact_v1",
+        "compact_v2",
+        "chunked_v1",
+        "chunked_v2",
+    ] {
+        let values = file.dataset(name)?.read::<i32, Ix2>()?;
+        assert!(values == array![[1, 2, 3], [4, 5, 6]]);
+    }
+    Ok(())
+}
+
+#[test]
+fn read_single_chunk_index() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    for name in &["single_chunk", "single_chunk_deflate"] {
+        let values = file.dataset(name)?.read::<i32, Ix2>()?;
+        assert!(values == array![[1, 2, 3], [4, 5, 6]]);
+    }
+    Ok(())
+}
+
+#[test]
+fn read_implicit_chunk_index() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    let values = file.dataset("implicit")?.read::<i32, Ix2>()?;
+    assert!(values == array![[0, 1, 2], [3, 4, 5], [6, 7, 8], [9, 10, 11], [12, 13, 14]]);
+    Ok(())
+}
+
+#[test]
+fn read_fixed_array_chunk_index() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    let values = file.dataset("fixed_array")?.read::<i16, Ix2>()?;
+    assert!(values == array![[0, 1, 2], [3, 4, 5], [0, 0, 8], [0, 0, 11], [12, 13, 14]]);
+    let paged = file.dataset("fixed_array_paged")?.read::<i32, Ix1>()?;
+    assert!(paged == array![0, 10, 20, 30, 0, 0, 0, 0, 80, 90]);
+    let filtered = file.dataset("fixed_array_deflate")?.read::<f64, Ix1>()?;
+    assert!(filtered == array![0.5, 1.5, 2.5]);
+    Ok(())
+}
+
+#[test]
+fn read_chunk_indexes_of_resizable_datasets() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    // Chunks are numbered along the maximum shape of 10 by 10, and the chunks
+    // outside the current shape are skipped
+    let expected = array![[0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11], [12, 13, 14, 15]];
+    let corner = Selection::Points {
+        coordinates: vec![vec![3, 2], vec![0, 1]],
+    };
+    for name in &["implicit_resizable", "fixed_array_resizable"] {
+        let dataset = file.dataset(name)?;
+        assert!(dataset.maxshape() == vec![Some(10), Some(10)]);
+        assert!(dataset.read::<i32, Ix2>()? == expected);
+        assert!(dataset.read_selection::<i32>(&corner)? == array![14, 1]);
+    }
+    Ok(())
+}
+
+#[test]
+fn open_unsupported_chunk_indexes() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    let extensible = file.dataset("extensible_array")?;
+    assert!(extensible.shape() == vec![3]);
+    assert!(extensible.maxshape() == vec![None]);
+    assert!(extensible.read::<i32, Ix1>().is_err());
+    let btree = file.dataset("btree_v2")?;
+    assert!(btree.maxshape() == vec![None, None]);
+    assert!(btree.read::<i32, Ix2>().is_err());
+    Ok(())
+}
+
+#[test]
+fn reject_invalid_chunk_dimension_lengths() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/layouts.h5")?;
+    assert!(file.dataset("encoded_length_0").is_err());
+    assert!(file.dataset("encoded_length_9").is_err());
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use ndarray::{array, Ix1};
+use oxifive::read::reference::{HyperslabBlock, ObjectReference, RegionReference, Selection};
+use oxifive::Object;
+
+mod common;
+use common::open;
+
+#[test]
+fn dereference_object_references() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/references.h5")?;
+    let references = file
+        .dataset("object_refs")?
+        .read::<ObjectReference, Ix1>()?;
+    match file.dereference(&references[0])? {
+        Object::Dataset(dataset) => {
+            assert!(dataset.read::<f64, Ix1>()? == array![0.5, 1.5, 2.5]);
+        }
+        _ => panic!("Expected the first reference to point to a dataset"),
+    }
+    assert!(matches!(
+        file.dereference(&references[1])?,
+        Object::Group(_)
+    ));
+    assert!(references[2].is_null());
+    assert!(file.dereference(&references[2]).is_err());
+    assert!(file
+        .dataset("data")?
+        .read::<ObjectReference, Ix1>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn read_region_references() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/regions.h5")?;
+    let references = file
+        .dataset("region_refs")?
+        .read::<RegionReference, Ix1>()?;
+    let series = file.read_region::<f64>(&references[0])?;
+    assert!(series == array![1.0, 1.5, 2.0, 3.5, 4.0]);
+    assert!(file.read_region::<i32>(&references[1])? == array![9, 3]);
+    assert!(file.read_region::<i32>(&references[2])? == array![1, 3, 9, 11]);
+    assert!(references[3].selection == Selection::All);
+    assert!(file.read_region::<f64>(&references[3])?.len() == 10);
+    assert!(file.read_region::<i32>(&references[4])? == array![4, 5]);
+    assert!(file.read_region::<f64>(&references[5])? == array![4.5, 0.0]);
+    // The last region extends past the end of the series
+    assert!(file.read_region::<f64>(&references[6]).is_err());
+    assert!(file.read_region::<i32>(&references[0]).is_err());
+    // Unlimited, empty or overflowing selections and values of invalid sizes
+    // cannot be read
+    let invalid = file.dataset("invalid_region_refs")?;
+    for index in 0..7 {
+        let element = Selection::Points {
+            coordinates: vec![vec![index]],
+        };
+        let region = invalid
+            .read_selection::<RegionReference>(&element)
+            .and_then(|references| file.read_region::<f64>(&references[0]));
+        assert!(region.is_err());
+    }
+    assert!(file
+        .dataset("region_refs")?
+        .read::<ObjectReference, Ix1>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn read_chunked_region_references() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/regions.h5")?;
+    let contiguous = file
+        .dataset("region_refs")?
+        .read::<RegionReference, Ix1>()?;
+    let chunked = file
+        .dataset("chunked_region_refs")?
+        .read::<RegionReference, Ix1>()?;
+    assert!(chunked == contiguous);
+    assert!(file.read_region::<i32>(&chunked[2])? == array![1, 3, 9, 11]);
+    Ok(())
+}
+
+#[test]
+fn read_regions_of_sparse_chunks() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/regions.h5")?;
+    let references = file
+        .dataset("region_refs")?
+        .read::<RegionReference, Ix1>()?;
+    // Only the chunks that hold selected elements are read, so the corrupt
+    // middle chunk is skipped and the unwritten last chunk holds zeros
+    assert!(file.read_region::<f64>(&references[7])? == array![1.5, 2.5, 0.0]);
+    assert!(file.read_region::<f64>(&references[8])? == array![0.0, 0.5]);
+    assert!(file.read_region::<f64>(&references[9]).is_err());
+    assert!(file.dataset("sparse_series")?.read::<f64, Ix1>().is_err());
+    Ok(())
+}
+
+#[test]
+fn merge_selected_ranges() -> Result<(), oxifive::error::Error> {
+    // Adjacent blocks of a long series merge into a single range
+    let series = Selection::RegularHyperslab {
+        start: vec![0],
+        stride: vec![1],
+        count: vec![1_000_000],
+        block: vec![1],
+    };
+    let ranges = series.ranges(&[1_000_000])?;
+    assert!(ranges.len() == 1 && ranges[0] == (0..1_000_000));
+    // Overlapping blocks select their shared elements once, row by row
+    let blocks = Selection::Hyperslab {
+        blocks: vec![
+            HyperslabBlock {
+                start: vec![0, 1],
+                end: vec![1, 2],
+            },
+            HyperslabBlock {
+                start: vec![1, 2],
+                end: vec![1, 3],
+            },
+        ],
+    };
+    assert!(blocks.ranges(&[3, 4])? == vec![1..3, 5..8]);
+    // Points keep their order
+    let points = Selection::Points {
+        coordinates: vec![vec![2], vec![3], vec![0]],
+    };
+    assert!(points.ranges(&[4])? == vec![2..4, 0..1]);
+    assert!(points.ranges(&[3]).is_err());
+    Ok(())
+}
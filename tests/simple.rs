@@ -1,5 +1,3 @@
-use std::{rc::Rc, cell::RefCell, io::Read, sync::{Arc, Mutex}};
-
 use ndarray::{array, Ix3};
 
 #[test]
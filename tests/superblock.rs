@@ -0,0 +1,80 @@
+use ndarray::{array, Ix2};
+use oxifive::read::superblock::SuperBlock;
+
+mod common;
+use common::open;
+
+#[test]
+fn read_superblock_v2() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/superblock_v2.h5")?;
+    assert!(matches!(file.superblock, SuperBlock::Version2(_)));
+    assert!(file.superblock.version() == 2);
+    let array = file.dataset("data")?.read::<f32, Ix2>()?;
+    assert!(array == array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    Ok(())
+}
+
+#[test]
+fn read_superblock_v3_with_extension() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/superblock_v3.h5")?;
+    assert!(file.superblock.version() == 3);
+    assert!(file.superblock_extension.is_some());
+    assert!(file.context.chunk_btree_k == 64);
+    let array = file.dataset("data")?.read::<f32, Ix2>()?;
+    assert!(array == array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    Ok(())
+}
+
+#[test]
+fn read_superblock_v1_chunked() -> Result<(), oxifive::error::Error> {
+    let file = open("tests/files/superblock_v1.h5")?;
+    assert!(file.superblock.version() == 1);
+    assert!(file.context.chunk_btree_k == 2);
+    let array = file.dataset("chunked")?.read::<f32, Ix2>()?;
+    assert!(
+        array
+            == array![
+                [0.0, 1.0, 2.0, 3.0],
+                [4.0, 5.0, 6.0, 7.0],
+                [8.0, 9.0, 10.0, 11.0],
+                [12.0, 13.0, 14.0, 15.0]
+            ]
+    );
+    Ok(())
+}
+
+#[test]
+fn read_small_offsets_and_lengths() -> Result<(), oxifive::error::Error> {
+    for filename in &[
+        "tests/files/offsets_2_lengths_2.h5",
+        "tests/files/offsets_4_lengths_4.h5",
+        "tests/files/offsets_4_lengths_8.h5",
+    ] {
+        let file = open(filename)?;
+        let contiguous = file.dataset("contiguous")?.read::<f32, Ix2>()?;
+        assert!(contiguous == array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let chunked = file
+            .group("group")?
+            .dataset("chunked")?
+            .read::<f32, Ix2>()?;
+        assert!(chunked[[3, 2]] == 14.0);
+    }
+    Ok(())
+}
+
+#[test]
+fn read_file_with_userblock() -> Result<(), oxifive::error::Error> {
+    for &(filename, userblock_size) in &[
+        ("tests/files/userblock_512.h5", 512),
+        ("tests/files/userblock_1024.h5", 1024),
+    ] {
+        let file = open(filename)?;
+        assert!(file.userblock_size() == userblock_size);
+        let userblock = file.userblock()?;
+        assert!(userblock.len() == userblock_size as usize);
+        assert!(userblock.starts_with(b"MATLAB 7.3 MAT-file"));
+        let array = file.dataset("data")?.read::<f32, Ix2>()?;
+        assert!(array == array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    }
+    Ok(())
+}